@@ -5,12 +5,16 @@ use axum::{
     Extension, Router,
 };
 use ymir::{
-    adapter::Adapter, context::Context, health, hook::LifeCycle, responses::Success, Result,
+    adapter::Adapter,
+    context::Context,
+    health,
+    hook::LifeCycle,
+    metrics::{prometheus_client::metrics::counter::Counter, Metrics, MetricsAdapter},
+    responses::Success,
+    Result,
 };
 use ymir_openapi::prelude::*;
 
-use crate::adapters::metrics::MetricsAdapter;
-
 pub struct App;
 #[async_trait]
 impl LifeCycle for App {
@@ -29,13 +33,43 @@ impl LifeCycle for App {
     }
 
     async fn adapters() -> Result<Vec<Box<dyn Adapter>>> {
-        Ok(vec![Box::new(MetricsAdapter::new("/metrics".to_string()))])
+        Ok(vec![Box::new(MetricsAdapter::default())])
+    }
+
+    fn openapi() -> Option<openapi::OpenApi> {
+        Some(App::doc().openapi().clone())
     }
 
     fn routes(ctx: Context) -> Router {
         let kunci = Kunci {
             label: String::from("kunci itu ada disini"),
+            lookups: Counter::default(),
         };
+        // The registry is set in the context by the metrics adapter.
+        if let Some(metrics) = ctx.get::<Metrics>() {
+            metrics.register(
+                "kunci_lookups",
+                "Number of kunci lookups",
+                kunci.lookups.clone(),
+            );
+        }
+        let expose_docs = ctx
+            .configs
+            .as_ref()
+            .is_some_and(|c| c.profile.expose_docs());
+        App::doc()
+            .expose_docs(expose_docs)
+            .route(
+                "/api/health-check-one",
+                axum::routing::get(|| async { "OK" }),
+            )
+            .layer(Extension(kunci))
+            .with_state(ctx)
+    }
+}
+
+impl App {
+    fn doc() -> RouterDoc<Context> {
         RouterDoc::new()
             .build_doc("/api/swagger", |mut doc| {
                 doc.info = openapi::Info::new(
@@ -47,13 +81,8 @@ impl LifeCycle for App {
                 doc
             })
             .routes(routes!(health::healthz))
+            .routes(routes!(health::adapters))
             .routes(routes!(health))
-            .route(
-                "/api/health-check-one",
-                axum::routing::get(|| async { "OK" }),
-            )
-            .layer(Extension(kunci))
-            .with_state(ctx)
     }
 }
 
@@ -67,6 +96,7 @@ impl LifeCycle for App {
     )
 )]
 pub async fn health(Extension(k): Extension<Kunci>) -> Result<Response> {
+    k.lookups.inc();
     Ok(Success {
         message: k.label.to_string(),
         status_code: StatusCode::OK.as_u16(),
@@ -77,4 +107,5 @@ pub async fn health(Extension(k): Extension<Kunci>) -> Result<Response> {
 #[derive(Clone)]
 pub struct Kunci {
     pub label: String,
+    pub lookups: Counter,
 }
@@ -2,5 +2,5 @@ use adaptor::app::App;
 
 #[tokio::main]
 async fn main() -> ymir::Result<()> {
-    ymir::startup::run::<App>().await
+    ymir::cli::main::<App>().await
 }
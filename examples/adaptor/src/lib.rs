@@ -1,2 +1 @@
-pub mod adapters;
 pub mod app;
@@ -28,10 +28,31 @@ impl LifeCycle for App {
         Ok(vec![])
     }
 
+    fn openapi() -> Option<openapi::OpenApi> {
+        Some(App::doc().openapi().clone())
+    }
+
     fn routes(ctx: Context) -> Router {
         let kunci = Kunci {
             label: String::from("kunci itu ada disini"),
         };
+        let expose_docs = ctx
+            .configs
+            .as_ref()
+            .is_some_and(|c| c.profile.expose_docs());
+        App::doc()
+            .expose_docs(expose_docs)
+            .route(
+                "/api/health-check-one",
+                axum::routing::get(|| async { "OK" }),
+            )
+            .layer(Extension(kunci))
+            .with_state(ctx)
+    }
+}
+
+impl App {
+    fn doc() -> RouterDoc<Context> {
         RouterDoc::new()
             .build_doc("/api/swagger", |mut doc| {
                 doc.info = openapi::Info::new(
@@ -43,12 +64,6 @@ impl LifeCycle for App {
                 doc
             })
             .routes(routes!(health))
-            .route(
-                "/api/health-check-one",
-                axum::routing::get(|| async { "OK" }),
-            )
-            .layer(Extension(kunci))
-            .with_state(ctx)
     }
 }
 
@@ -2,5 +2,5 @@ use simple::app::App;
 
 #[tokio::main]
 async fn main() -> ymir::Result<()> {
-    ymir::startup::run::<App>().await
+    ymir::cli::main::<App>().await
 }
@@ -1,16 +1,20 @@
 pub mod prelude;
 pub mod router;
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::{Arc, OnceLock},
+};
 
 use axum::{
     response::Html,
     routing::{self, MethodFilter},
-    Router,
+    Extension, Router,
 };
 use serde::Serialize;
 use serde_json::Value;
 use utoipa::openapi::{HttpMethod, OpenApi};
+use ymir::adapter::AdapterOpenApi;
 
 const DEFAULT_HTML: &str = include_str!("./assets/swagger.html");
 
@@ -32,12 +36,31 @@ impl<S: Spec> Swagger<S> {
     }
 
     pub fn to_html(&self) -> String {
-        self.html.replace(
-            "$spec",
-            &serde_json::to_string(&self.openapi).expect(
-                "Invalid OpenAPI spec, expected OpenApi, String, &str or serde_json::Value",
-            ),
-        )
+        render(&self.html, &self.spec())
+    }
+
+    fn spec(&self) -> Value {
+        serde_json::to_value(&self.openapi)
+            .expect("Invalid OpenAPI spec, expected OpenApi, String, &str or serde_json::Value")
+    }
+}
+
+fn render(html: &str, spec: &Value) -> String {
+    html.replace("$spec", &spec.to_string())
+}
+
+/// Merge the paths and components contributed by the adapters, a spec that
+/// is not an OpenAPI document is left as is.
+fn merge_adapters(spec: &Value, adapters: Option<&AdapterOpenApi>) -> Value {
+    let Some(AdapterOpenApi(adapters)) = adapters else {
+        return spec.clone();
+    };
+    match serde_json::from_value::<OpenApi>(spec.clone()) {
+        Ok(mut doc) => {
+            doc.merge(adapters.as_ref().clone());
+            serde_json::to_value(doc).unwrap_or_else(|_| spec.clone())
+        }
+        Err(_) => spec.clone(),
     }
 }
 
@@ -77,9 +100,22 @@ impl<S: Spec, R> From<Swagger<S>> for Router<R>
 where
     R: Clone + Send + Sync + 'static,
 {
+    /// The adapters contributions are merged on the first request, from the
+    /// [`AdapterOpenApi`] extension set by ymir.
     fn from(value: Swagger<S>) -> Self {
-        let html = value.to_html();
-        Router::<R>::new().route(&value.url.as_ref(), routing::get(|| async { Html(html) }))
+        let spec = Arc::new(value.spec());
+        let template = value.html.clone();
+        let html = Arc::new(OnceLock::new());
+        Router::<R>::new().route(
+            value.url.as_ref(),
+            routing::get(|adapters: Option<Extension<AdapterOpenApi>>| async move {
+                let html = html.get_or_init(|| {
+                    let adapters = adapters.as_ref().map(|Extension(doc)| doc);
+                    render(&template, &merge_adapters(&spec, adapters))
+                });
+                Html(html.clone())
+            }),
+        )
     }
 }
 
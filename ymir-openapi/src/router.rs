@@ -1,7 +1,7 @@
 use std::{borrow::Cow, convert::Infallible};
 
 use axum::{routing::MethodRouter, Router};
-use utoipa::{openapi, OpenApi};
+use utoipa::openapi;
 
 use crate::{Servable, Swagger};
 
@@ -24,8 +24,15 @@ pub type OpenApiMethod<S = (), E = Infallible> = (
     axum::routing::MethodRouter<S, E>,
 );
 
+/// Router collecting the OpenAPI document of its routes, the documentation
+/// UI is served at the path given to [`RouterDoc::build_doc`] unless
+/// [`RouterDoc::expose_docs`] hides it.
 #[derive(Clone)]
-pub struct RouterDoc<S = ()>(Router<S>, utoipa::openapi::OpenApi, Cow<'static, str>);
+pub struct RouterDoc<S = ()>(
+    Router<S>,
+    utoipa::openapi::OpenApi,
+    Option<Cow<'static, str>>,
+);
 
 impl<S> RouterDoc<S>
 where
@@ -39,7 +46,7 @@ where
         Self(
             Router::new(),
             ApiDoc::openapi(),
-            Cow::Borrowed(DEFAULT_OPENAPI),
+            Some(Cow::Borrowed(DEFAULT_OPENAPI)),
         )
     }
 
@@ -48,7 +55,17 @@ where
         path: U,
         build_doc: fn(oa: openapi::OpenApi) -> openapi::OpenApi,
     ) -> Self {
-        Self(self.0, build_doc(self.1), path.into())
+        Self(self.0, build_doc(self.1), self.2.map(|_| path.into()))
+    }
+
+    /// Serve the documentation UI, the document is still built and returned
+    /// by [`RouterDoc::openapi`] when it is hidden.
+    pub fn expose_docs(self, expose: bool) -> Self {
+        let path = self
+            .2
+            .filter(|_| expose)
+            .or_else(|| expose.then_some(Cow::Borrowed(DEFAULT_OPENAPI)));
+        Self(self.0, self.1, path)
     }
 
     pub fn routes(mut self, (schemas, mut paths, method_router): OpenApiMethod<S>) -> Self {
@@ -79,6 +96,11 @@ where
         Self(router, self.1, self.2)
     }
 
+    /// The OpenAPI document built so far.
+    pub fn openapi(&self) -> &openapi::OpenApi {
+        &self.1
+    }
+
     /// Pass through method for [`axum::Router<S>::route`].
     pub fn route(self, path: &str, method_router: MethodRouter<S>) -> Router<S> {
         self.0.clone().merge(Self(
@@ -89,11 +111,23 @@ where
     }
 }
 
+impl<S> Default for RouterDoc<S>
+where
+    S: Send + Sync + Clone + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<S> From<RouterDoc<S>> for Router<S>
 where
     S: Clone + Send + Sync + 'static,
 {
     fn from(value: RouterDoc<S>) -> Self {
-        value.0.merge(Swagger::with_url(value.2, value.1))
+        match value.2 {
+            Some(path) => value.0.merge(Swagger::with_url(path, value.1)),
+            None => value.0,
+        }
     }
 }
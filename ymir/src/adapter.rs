@@ -1,34 +1,343 @@
 use async_trait::async_trait;
 use axum::Router;
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fmt::Debug;
-use tokio::sync::broadcast;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+use tokio::{sync::broadcast, task::JoinSet};
+use tracing::Instrument;
+use utoipa::{openapi::OpenApi, ToSchema};
 
-use crate::{context::Context, Result};
+use crate::{
+    config::{Config, ServerHealth},
+    context::Context,
+    errors,
+    event::{EventBus, Subscriber},
+    scheduler::Job,
+    signal::ShutdownPhase,
+    timeline::StartupTimeline,
+    worker::Backoff,
+    Result,
+};
 
-/// Represents the current state of an adapter
-#[derive(Debug, Clone, PartialEq)]
+/// Represents the current state of an adapter, tracked by the
+/// [`AdapterManager`] through the lifecycle hooks
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum AdapterState {
+    /// Registered, `init` did not complete yet
+    Registered,
+    /// Not started because an optional dependency failed
+    Skipped,
+    /// Disabled by `adapters.<key>.enable` and never initialized
+    Disabled,
     Initialized,
     Running,
     Stopped,
     Failed,
 }
 
-/// Priority level for adapter execution order
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub enum AdapterPriority {
-    High = 0,
-    Normal = 1,
-    Low = 2,
+/// OpenAPI paths and components contributed by the adapters, merged into
+/// the document served by the Swagger UI of `ymir-openapi`.
+#[derive(Clone)]
+pub struct AdapterOpenApi(pub Arc<OpenApi>);
+
+/// Timeouts of the adapter lifecycle hooks, `None` waits forever
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookTimeouts {
+    pub init: Option<Duration>,
+    pub before_run: Option<Duration>,
+    pub before_stop: Option<Duration>,
+    pub after_stop: Option<Duration>,
 }
 
-impl Default for AdapterPriority {
+/// How the [`AdapterManager`] reacts to the failures of an adapter.
+///
+/// A hook fails when it returns an error that [`Adapter::handle_error`] does
+/// not handle, or when it exceeds its timeout.
+///
+/// Example
+/// ```rust
+/// use std::time::Duration;
+/// use ymir::{adapter::AdapterPolicy, worker::Backoff};
+///
+/// let policy = AdapterPolicy {
+///     init_retry: Some(Backoff {
+///         max_retries: Some(5),
+///         ..Backoff::default()
+///     }),
+///     ..AdapterPolicy::optional()
+/// };
+/// assert!(!policy.required);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterPolicy {
+    /// A required adapter failing stops the startup and makes `/readyz`
+    /// report not ready, an optional one is skipped with its dependents.
+    pub required: bool,
+    /// Retry a failed `init` with an exponential backoff
+    pub init_retry: Option<Backoff>,
+    pub timeouts: HookTimeouts,
+}
+
+impl Default for AdapterPolicy {
     fn default() -> Self {
-        Self::Normal
+        Self {
+            required: true,
+            init_retry: None,
+            timeouts: HookTimeouts::default(),
+        }
+    }
+}
+
+impl AdapterPolicy {
+    /// Policy of an adapter the application can run without
+    pub fn optional() -> Self {
+        Self {
+            required: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// Section `adapters.<key>` of the configuration given to
+/// [`Adapter::configure`], without its `enable` field.
+///
+/// Example
+/// ```rust
+/// use serde::Deserialize;
+/// use ymir::adapter::AdapterConfig;
+///
+/// #[derive(Deserialize)]
+/// struct DatabaseConfig {
+///     url: String,
+///     #[serde(default)]
+///     max_connections: u32,
+/// }
+///
+/// let section = serde_json::json!({ "url": "postgres://localhost/app" });
+/// let config = AdapterConfig::new("database", &section);
+/// let database: DatabaseConfig = config.bind().unwrap();
+/// assert_eq!(database.max_connections, 0);
+///
+/// let section = serde_json::json!({ "url": "postgres://localhost/app", "max_connections": "ten" });
+/// let err = AdapterConfig::new("database", &section).bind::<DatabaseConfig>().err().unwrap();
+/// assert!(err.to_string().starts_with("invalid configuration `adapters.database.max_connections`"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AdapterConfig<'a> {
+    key: &'a str,
+    section: &'a serde_json::Value,
+}
+
+impl<'a> AdapterConfig<'a> {
+    pub fn new(key: &'a str, section: &'a serde_json::Value) -> Self {
+        Self { key, section }
+    }
+
+    pub fn key(&self) -> &str {
+        self.key
+    }
+
+    /// The section is missing or holds no setting
+    pub fn is_empty(&self) -> bool {
+        match self.section {
+            serde_json::Value::Null => true,
+            serde_json::Value::Object(map) => map.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Deserialize the section into the adapter configuration type, a
+    /// missing section is read as an empty map. The error names the path of
+    /// the malformed setting.
+    pub fn bind<T: DeserializeOwned>(&self) -> Result<T> {
+        let empty = serde_json::Value::Object(serde_json::Map::new());
+        let section = if self.section.is_null() {
+            &empty
+        } else {
+            self.section
+        };
+        serde_path_to_error::deserialize(section).map_err(|e| {
+            let path = e.path().to_string();
+            let path = if path == "." {
+                format!("adapters.{}", self.key)
+            } else {
+                format!("adapters.{}.{path}", self.key)
+            };
+            errors::Error::Message(format!(
+                "invalid configuration `{path}`: {}",
+                e.into_inner()
+            ))
+        })
+    }
+}
+
+/// Result of the last [`Adapter::health`] check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AdapterHealth {
+    pub healthy: bool,
+    pub error: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub checked_at: DateTime<Utc>,
+}
+
+/// State of an adapter at a point in time
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AdapterStatus {
+    pub name: String,
+    pub state: AdapterState,
+    /// Whether the adapter is required for the application to be ready
+    pub required: bool,
+    /// Error of the hook that failed, when the state is `failed`
+    pub error: Option<String>,
+    /// When the adapter entered its current state
+    #[schema(value_type = String, format = DateTime)]
+    pub since: DateTime<Utc>,
+    /// Last health check, `None` until the adapter was checked
+    pub health: Option<AdapterHealth>,
+    /// Duration in seconds of the lifecycle hooks run so far, keyed by hook
+    pub timings: BTreeMap<String, f64>,
+}
+
+impl AdapterStatus {
+    /// Not failed and not reported unhealthy by its last check
+    pub fn is_healthy(&self) -> bool {
+        self.state != AdapterState::Failed && self.health.as_ref().is_none_or(|h| h.healthy)
+    }
+}
+
+/// Shared view of the adapters state, kept up to date by the
+/// [`AdapterManager`] and available from the context of the application.
+#[derive(Debug, Clone, Default)]
+pub struct AdapterStatuses(Arc<RwLock<Vec<AdapterStatus>>>);
+
+impl AdapterStatuses {
+    /// Status of every adapter, in execution order
+    pub fn snapshot(&self) -> Vec<AdapterStatus> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Status of the adapter named `name`
+    pub fn get(&self, name: &str) -> Option<AdapterStatus> {
+        self.snapshot().into_iter().find(|s| s.name == name)
+    }
+
+    /// Every required adapter is healthy
+    pub fn is_ready(&self) -> bool {
+        self.snapshot()
+            .iter()
+            .all(|s| !s.required || s.is_healthy())
+    }
+
+    fn register(&self, name: String, required: bool) {
+        let mut statuses = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        statuses.retain(|s| s.name != name);
+        statuses.push(AdapterStatus {
+            name,
+            state: AdapterState::Registered,
+            required,
+            error: None,
+            since: Utc::now(),
+            health: None,
+            timings: BTreeMap::new(),
+        });
+    }
+
+    fn skip_dependent(
+        &self,
+        adapter: &dyn Adapter,
+        policy: &AdapterPolicy,
+        failed: &[String],
+    ) -> Result<bool> {
+        let name = adapter.name();
+        let Some(dependency) = adapter
+            .depends_on()
+            .into_iter()
+            .find(|d| failed.contains(d))
+        else {
+            return Ok(false);
+        };
+        let error = format!("dependency `{dependency}` failed");
+        if policy.required {
+            self.transition(&name, AdapterState::Failed, Some(error));
+            return Err(errors::Error::Message(format!(
+                "adapter `{name}` depends on `{dependency}` which failed"
+            )));
+        }
+        self.transition(&name, AdapterState::Skipped, Some(error));
+        Ok(true)
+    }
+
+    fn timing(&self, name: &str, hook: &str, elapsed: Duration) {
+        let mut statuses = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(status) = statuses.iter_mut().find(|s| s.name == name) {
+            status
+                .timings
+                .insert(hook.to_string(), elapsed.as_secs_f64());
+        }
+    }
+
+    fn set_health(&self, name: &str, health: AdapterHealth) {
+        let mut statuses = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(status) = statuses.iter_mut().find(|s| s.name == name) {
+            status.health = Some(health);
+        }
+    }
+
+    /// Sort the statuses following the adapters order
+    fn reorder(&self, names: &[String]) {
+        let mut statuses = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        statuses.sort_by_key(|s| {
+            names
+                .iter()
+                .position(|n| *n == s.name)
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    fn transition(&self, name: &str, state: AdapterState, error: Option<String>) {
+        let mut statuses = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(status) = statuses.iter_mut().find(|s| s.name == name) {
+            status.state = state;
+            status.error = error;
+            status.since = Utc::now();
+        }
+    }
+
+    fn set(&self, name: &str, state: AdapterState) {
+        self.transition(name, state, None);
+    }
+
+    /// Record the result of a hook, the adapter fails when the error was not
+    /// handled by [`Adapter::handle_error`]
+    fn record<T>(&self, name: &str, hook: &str, result: &Result<T>, state: AdapterState) {
+        match result {
+            Ok(_) => self.set(name, state),
+            Err(e) => {
+                tracing::error!(adapter = name, hook, error = %e, "adapter failed");
+                self.transition(name, AdapterState::Failed, Some(format!("{hook}: {e}")));
+            }
+        }
     }
 }
 
+/// Priority level for adapter execution order, it only orders adapters
+/// without dependency between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdapterPriority {
+    High = 0,
+    #[default]
+    Normal = 1,
+    Low = 2,
+}
+
 // Enhanced Adapter trait
 #[async_trait]
 pub trait Adapter: Send + Sync + Debug {
@@ -40,10 +349,32 @@ pub trait Adapter: Send + Sync + Debug {
         AdapterPriority::default()
     }
 
-    /// Get the current state of the adapter
-    fn state(&self) -> AdapterState;
+    /// Failure policy of the adapter, required without retry nor timeout
+    /// by default
+    fn policy(&self) -> AdapterPolicy {
+        AdapterPolicy::default()
+    }
+
+    /// Names of the adapters that must run their hooks before this one, and
+    /// stop after it
+    fn depends_on(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Key of the adapter section in the `adapters` configuration, defaults
+    /// to the adapter name
+    fn config_key(&self) -> String {
+        self.name()
+    }
 
-    /// Initialize the adapter
+    /// Receive the `adapters.<key>` section before `init`, usually bound to
+    /// the adapter configuration type with [`AdapterConfig::bind`]
+    fn configure(&mut self, _config: AdapterConfig<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Initialize the adapter, concurrently with the adapters that are
+    /// independent of it
     async fn init(&mut self) -> Result<()> {
         Ok(())
     }
@@ -58,6 +389,51 @@ pub trait Adapter: Send + Sync + Debug {
         Ok(router.clone())
     }
 
+    /// OpenAPI paths and components of the routes mounted by `after_route`
+    ///
+    /// Example
+    /// ```rust,ignore
+    /// #[derive(utoipa::OpenApi)]
+    /// #[openapi(paths(metrics))]
+    /// struct MetricsDoc;
+    ///
+    /// fn openapi(&self) -> Option<OpenApi> {
+    ///     Some(MetricsDoc::openapi())
+    /// }
+    /// ```
+    fn openapi(&self) -> Option<OpenApi> {
+        None
+    }
+
+    /// Jobs scheduled next to the HTTP server, registered once `before_run`
+    /// completed
+    fn jobs(&self) -> Vec<Job> {
+        vec![]
+    }
+
+    /// Subscribers of the [`crate::event::EventBus`], registered once
+    /// `before_run` completed
+    fn subscribers(&self) -> Vec<Subscriber> {
+        vec![]
+    }
+
+    /// Check the adapter can serve requests, e.g. ping its connection pool.
+    /// Called periodically once the adapter runs, within
+    /// `server.health.timeout`
+    async fn health(&self, _ctx: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when a new configuration is published by a live reload
+    async fn on_config_change(&self, _ctx: &Context, _config: &Config) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called at each phase of the graceful shutdown, before `before_stop`
+    async fn on_shutdown(&self, _ctx: &Context, _phase: ShutdownPhase) -> Result<()> {
+        Ok(())
+    }
+
     /// Called when the server is shutting down
     async fn before_stop(&self, _ctx: &Context) -> Result<()> {
         Ok(())
@@ -79,85 +455,476 @@ pub struct AdapterManager {
     adapters: Vec<Box<dyn Adapter>>,
     ctx: Context,
     shutdown_tx: broadcast::Sender<()>,
+    statuses: AdapterStatuses,
+    timeline: StartupTimeline,
 }
 
 impl AdapterManager {
-    pub fn new(ctx: Context) -> Self {
+    /// The [`AdapterStatuses`] of the manager is set in `ctx`
+    pub fn new(mut ctx: Context) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
+        let statuses = AdapterStatuses::default();
+        ctx.set(statuses.clone());
+        let timeline = ctx.get::<StartupTimeline>().cloned().unwrap_or_default();
         Self {
             adapters: Vec::new(),
             ctx,
             shutdown_tx,
+            statuses,
+            timeline,
         }
     }
 
     /// Register a new adapter
     pub fn register(&mut self, adapter: Box<dyn Adapter>) {
+        self.statuses
+            .register(adapter.name(), adapter.policy().required);
         self.adapters.push(adapter);
         // Sort adapters by priority
         self.adapters.sort_by_key(|a| a.priority());
     }
 
-    /// Initialize all registered adapters
+    /// Registered adapters in execution order
+    pub fn adapters(&self) -> &[Box<dyn Adapter>] {
+        &self.adapters
+    }
+
+    /// Shared view of the adapters state
+    pub fn statuses(&self) -> AdapterStatuses {
+        self.statuses.clone()
+    }
+
+    /// Status of every adapter, in execution order
+    pub fn status(&self) -> Vec<AdapterStatus> {
+        self.statuses.snapshot()
+    }
+
+    /// Give each adapter its `adapters.<key>` section, and drop the adapters
+    /// disabled with `adapters.<key>.enable: false`.
+    ///
+    /// Fails when a section is malformed or when an enabled adapter depends
+    /// on a disabled one.
+    pub fn configure(&mut self) -> Result<()> {
+        let sections = self
+            .ctx
+            .configs
+            .as_ref()
+            .and_then(|c| c.adapters.clone())
+            .unwrap_or_default();
+
+        let mut enabled = vec![];
+        let mut disabled = vec![];
+        for adapter in std::mem::take(&mut self.adapters) {
+            let key = adapter.config_key();
+            let mut section = sections.get(&key).cloned().unwrap_or_default();
+            let enable = match &mut section {
+                serde_json::Value::Object(map) => map.remove("enable"),
+                _ => None,
+            };
+            if adapter_enabled(&key, enable)? {
+                enabled.push((adapter, key, section));
+            } else {
+                tracing::info!(adapter = adapter.name(), "adapter disabled");
+                self.statuses.set(&adapter.name(), AdapterState::Disabled);
+                disabled.push(adapter.name());
+            }
+        }
+
+        for (mut adapter, key, section) in enabled {
+            if let Some(dependency) = adapter.depends_on().iter().find(|d| disabled.contains(d)) {
+                return Err(errors::Error::Message(format!(
+                    "adapter `{}` depends on `{dependency}` which is disabled",
+                    adapter.name()
+                )));
+            }
+            let configured = adapter.configure(AdapterConfig::new(&key, &section));
+            self.statuses.record(
+                &adapter.name(),
+                "configure",
+                &configured,
+                AdapterState::Registered,
+            );
+            configured?;
+            self.adapters.push(adapter);
+        }
+        Ok(())
+    }
+
+    /// Sort the adapters so each one comes after its dependencies, adapters
+    /// of the same dependency level keep the priority order.
+    ///
+    /// Returns the dependency level of each adapter, in the sorted order.
+    /// Fails when a dependency is not registered or on a dependency cycle.
+    pub fn resolve(&mut self) -> Result<Vec<usize>> {
+        let levels = dependency_levels(&self.adapters)?;
+        let mut ordered: Vec<_> = std::mem::take(&mut self.adapters)
+            .into_iter()
+            .zip(levels)
+            .enumerate()
+            .collect();
+        // Registration keeps the priority order, the sort is stable.
+        ordered.sort_by_key(|(_, (_, level))| *level);
+        let (adapters, levels) = ordered.into_iter().map(|(_, entry)| entry).unzip();
+        self.adapters = adapters;
+        let names: Vec<_> = self.adapters.iter().map(|a| a.name()).collect();
+        self.statuses.reorder(&names);
+        Ok(levels)
+    }
+
+    /// Initialize all registered adapters, level by level of the dependency
+    /// graph. Adapters of the same level are initialized concurrently.
+    ///
+    /// A failing optional adapter is skipped with its dependents, following
+    /// its [`AdapterPolicy`].
     pub async fn init_all(&mut self) -> Result<()> {
+        let timeline = self.timeline.clone();
+        timeline.step("adapters configure", || self.configure())?;
+        let levels = self.resolve()?;
         tracing::info!(adapters = ?self.adapters.iter().map(|init| init.name()).collect::<Vec<_>>().join(","), "init adapter");
-        for adapter in &mut self.adapters {
-            if let Err(e) = adapter.init().await {
-                adapter.handle_error(Box::new(e)).await?;
+
+        let names: Vec<_> = self.adapters.iter().map(|a| a.name()).collect();
+        let policies: Vec<_> = self.adapters.iter().map(|a| a.policy()).collect();
+        let mut slots: Vec<_> = std::mem::take(&mut self.adapters)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut failed = vec![];
+        let mut result = Ok(());
+
+        for level in 0..=levels.iter().copied().max().unwrap_or(0) {
+            let mut tasks = JoinSet::new();
+            let mut indexes = HashMap::new();
+            for (i, slot) in slots.iter_mut().enumerate() {
+                if levels[i] != level {
+                    continue;
+                }
+                let Some(mut adapter) = slot.take() else {
+                    continue;
+                };
+                match self
+                    .statuses
+                    .skip_dependent(adapter.as_ref(), &policies[i], &failed)
+                {
+                    Ok(false) => {}
+                    Ok(true) => {
+                        failed.push(names[i].clone());
+                        continue;
+                    }
+                    Err(e) => {
+                        result = result.and(Err(e));
+                        continue;
+                    }
+                }
+                let policy = policies[i].clone();
+                let task = tasks.spawn(async move {
+                    let started = Instant::now();
+                    let result = init_adapter(adapter.as_mut(), &policy).await;
+                    (adapter, result, started)
+                });
+                indexes.insert(task.id(), i);
+            }
+
+            while let Some(joined) = tasks.join_next_with_id().await {
+                let (i, init) = match joined {
+                    Ok((id, (adapter, init, started))) => {
+                        let i = indexes[&id];
+                        record_timing(&self.statuses, &self.timeline, &names[i], "init", started);
+                        slots[i] = Some(adapter);
+                        (i, init)
+                    }
+                    Err(e) => {
+                        let i = indexes[&e.id()];
+                        let panicked = Err(errors::Error::Message(format!(
+                            "adapter `{}` panicked during init: {e}",
+                            names[i]
+                        )));
+                        (i, panicked)
+                    }
+                };
+                self.statuses
+                    .record(&names[i], "init", &init, AdapterState::Initialized);
+                if init.is_err() && !policies[i].required {
+                    slots[i] = None;
+                    failed.push(names[i].clone());
+                    continue;
+                }
+                result = result.and(init);
+            }
+            if result.is_err() {
+                break;
             }
         }
-        Ok(())
+
+        self.adapters = slots.into_iter().flatten().collect();
+        result
     }
 
-    /// Run before_run on all adapters
+    /// Run before_run on all adapters, a failing optional adapter is skipped
+    /// with its dependents
     pub async fn before_run(&mut self) -> Result<Context> {
         tracing::info!(adapters = ?self.adapters.iter().map(|init| init.name()).collect::<Vec<_>>().join(","), "before run adapter");
         let mut current_ctx = self.ctx.clone();
+        let mut failed = vec![];
         for adapter in &mut self.adapters {
-            match adapter.before_run(current_ctx.clone()).await {
-                Ok(ctx) => current_ctx = ctx,
-                Err(e) => {
-                    adapter.handle_error(Box::new(e)).await?;
+            let name = adapter.name();
+            let policy = adapter.policy();
+            if self
+                .statuses
+                .skip_dependent(adapter.as_ref(), &policy, &failed)?
+            {
+                failed.push(name);
+                continue;
+            }
+            let started = Instant::now();
+            let before_run = within(
+                &name,
+                "before_run",
+                policy.timeouts.before_run,
+                adapter.before_run(current_ctx.clone()),
+            )
+            .await;
+            record_timing(&self.statuses, &self.timeline, &name, "before_run", started);
+            let result = match before_run {
+                Ok(ctx) => {
+                    current_ctx = ctx;
+                    Ok(())
                 }
+                Err(e) => adapter.handle_error(Box::new(e)).await,
+            };
+            self.statuses
+                .record(&name, "before_run", &result, AdapterState::Running);
+            if result.is_err() && !policy.required {
+                failed.push(name);
+                continue;
             }
+            result?;
         }
+        self.adapters.retain(|a| !failed.contains(&a.name()));
+        self.log_skipped();
         Ok(current_ctx)
     }
 
+    /// Summary of the optional adapters the application runs without
+    fn log_skipped(&self) {
+        let skipped: Vec<_> = self
+            .status()
+            .into_iter()
+            .filter(|s| matches!(s.state, AdapterState::Failed | AdapterState::Skipped))
+            .map(|s| format!("{} ({})", s.name, s.error.unwrap_or_default()))
+            .collect();
+        if !skipped.is_empty() {
+            tracing::warn!(
+                skipped = skipped.join(", "),
+                "optional adapters skipped, the application runs without them"
+            );
+        }
+    }
+
     /// Configure routes through all adapters
     pub async fn configure_routes(&self, mut router: Router) -> Result<Router> {
         tracing::info!(adapters = ?self.adapters.iter().map(|init| init.name()).collect::<Vec<_>>().join(","), "after router adapter");
         for adapter in &self.adapters {
-            match adapter.after_route(&self.ctx, router.clone()).await {
+            let name = adapter.name();
+            let started = Instant::now();
+            let after_route = adapter
+                .after_route(&self.ctx, router.clone())
+                .instrument(tracing::info_span!(
+                    "adapter",
+                    adapter = name,
+                    hook = "after_route"
+                ))
+                .await;
+            record_timing(
+                &self.statuses,
+                &self.timeline,
+                &name,
+                "after_route",
+                started,
+            );
+            match after_route {
                 Ok(r) => router = r,
                 Err(e) => {
-                    adapter.handle_error(Box::new(e)).await?;
+                    let result = adapter.handle_error(Box::new(e)).await;
+                    if result.is_err() {
+                        self.statuses
+                            .record(&name, "after_route", &result, AdapterState::Running);
+                    }
+                    result?;
                 }
             }
         }
         Ok(router)
     }
 
-    /// Gracefully stop all adapters
+    /// OpenAPI document merging the contributions of all adapters, `None`
+    /// when no adapter contributes
+    pub fn openapi(&self) -> Option<OpenApi> {
+        self.adapters
+            .iter()
+            .filter_map(|a| a.openapi())
+            .reduce(|mut doc, other| {
+                doc.merge(other);
+                doc
+            })
+    }
+
+    /// Jobs of all adapters
+    pub fn jobs(&self) -> Vec<Job> {
+        self.adapters.iter().flat_map(|a| a.jobs()).collect()
+    }
+
+    /// Event subscribers of the running adapters
+    pub fn subscribers(&self) -> Vec<Subscriber> {
+        self.adapters.iter().flat_map(|a| a.subscribers()).collect()
+    }
+
+    /// Run the health check of every adapter concurrently and record the
+    /// results, returns whether every required adapter is healthy
+    pub async fn check_health(&self) -> bool {
+        let timeout = Duration::from_millis(self.health_config().timeout);
+        let checks = self.adapters.iter().map(|adapter| async move {
+            let error = match tokio::time::timeout(timeout, adapter.health(&self.ctx)).await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some(format!("health check timed out after {timeout:?}")),
+            };
+            if let Some(error) = &error {
+                tracing::warn!(adapter = adapter.name(), error, "adapter unhealthy");
+            }
+            self.statuses.set_health(
+                &adapter.name(),
+                AdapterHealth {
+                    healthy: error.is_none(),
+                    error,
+                    checked_at: Utc::now(),
+                },
+            );
+        });
+        futures_util::future::join_all(checks).await;
+        self.statuses.is_ready()
+    }
+
+    /// Check the adapters health every `server.health.interval`. Never
+    /// returns.
+    pub async fn watch_health(&self) {
+        let interval = self.health_config().interval;
+        if interval == 0 {
+            return std::future::pending().await;
+        }
+        let mut ticks = tokio::time::interval(Duration::from_millis(interval));
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            self.check_health().await;
+        }
+    }
+
+    fn health_config(&self) -> ServerHealth {
+        self.ctx
+            .configs
+            .as_ref()
+            .map(|c| c.server.health.clone())
+            .unwrap_or_default()
+    }
+
+    /// Notify all adapters of a reloaded configuration, failures are logged
+    /// and do not stop the other adapters
+    pub async fn notify_config_change(&self, config: &Config) {
+        for adapter in &self.adapters {
+            let notified = match adapter.on_config_change(&self.ctx, config).await {
+                Ok(()) => Ok(()),
+                Err(e) => adapter.handle_error(Box::new(e)).await,
+            };
+            if let Err(e) = notified {
+                tracing::error!(adapter = adapter.name(), error = %e, "failed to apply configuration change");
+            }
+        }
+    }
+
+    /// Notify all adapters of a graceful shutdown phase, in stop order
+    pub async fn notify_shutdown(&self, phase: ShutdownPhase) -> Result<()> {
+        tracing::info!(?phase, "shutdown phase");
+        for adapter in self.adapters.iter().rev() {
+            if let Err(e) = adapter.on_shutdown(&self.ctx, phase).await {
+                adapter.handle_error(Box::new(e)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gracefully stop all adapters, dependents before their dependencies
     pub async fn stop_all(&self) -> Result<()> {
         // Notify all adapters of impending shutdown
         let _ = self.shutdown_tx.send(());
 
+        // Pending events are handled while the adapters still run.
+        if let Some(events) = self.ctx.get::<EventBus>() {
+            events.drain().await;
+        }
+
+        // Every adapter is stopped even when one fails, the first failure
+        // of a required adapter is returned.
+        let mut result = Ok(());
+
         // Call before_stop on all adapters
-        for adapter in &self.adapters {
-            if let Err(e) = adapter.before_stop(&self.ctx).await {
-                adapter.handle_error(Box::new(e)).await?;
+        for adapter in self.adapters.iter().rev() {
+            let name = adapter.name();
+            let policy = adapter.policy();
+            let started = Instant::now();
+            let before_stop = within(
+                &name,
+                "before_stop",
+                policy.timeouts.before_stop,
+                adapter.before_stop(&self.ctx),
+            )
+            .await;
+            record_timing(
+                &self.statuses,
+                &self.timeline,
+                &name,
+                "before_stop",
+                started,
+            );
+            if let Err(e) = before_stop {
+                let handled = adapter.handle_error(Box::new(e)).await;
+                if handled.is_err() {
+                    self.statuses
+                        .record(&name, "before_stop", &handled, AdapterState::Stopped);
+                    if policy.required {
+                        result = result.and(handled);
+                    }
+                }
             }
         }
 
         // Call after_stop on all adapters
-        for adapter in &self.adapters {
-            if let Err(e) = adapter.after_stop(self.ctx.clone()).await {
-                adapter.handle_error(Box::new(e)).await?;
+        for adapter in self.adapters.iter().rev() {
+            let name = adapter.name();
+            let policy = adapter.policy();
+            let started = Instant::now();
+            let after_stop = within(
+                &name,
+                "after_stop",
+                policy.timeouts.after_stop,
+                adapter.after_stop(self.ctx.clone()),
+            )
+            .await;
+            record_timing(&self.statuses, &self.timeline, &name, "after_stop", started);
+            let stopped = match after_stop {
+                Ok(()) => Ok(()),
+                Err(e) => adapter.handle_error(Box::new(e)).await,
+            };
+            // Keep the error of a failed before_stop.
+            if stopped.is_err() || self.statuses.get(&name).is_none_or(|s| s.error.is_none()) {
+                self.statuses
+                    .record(&name, "after_stop", &stopped, AdapterState::Stopped);
+            }
+            if policy.required {
+                result = result.and(stopped);
             }
         }
 
-        Ok(())
+        result
     }
 
     /// Get a reference to the shutdown broadcast channel
@@ -166,17 +933,162 @@ impl AdapterManager {
     }
 }
 
+/// Record the duration of a hook in the status of the adapter and the
+/// startup timeline.
+fn record_timing(
+    statuses: &AdapterStatuses,
+    timeline: &StartupTimeline,
+    name: &str,
+    hook: &str,
+    started: Instant,
+) {
+    let elapsed = started.elapsed();
+    statuses.timing(name, hook, elapsed);
+    timeline.record(format!("adapter {name} {hook}"), started, elapsed);
+}
+
+/// Run a hook of the adapter `name` within `timeout`.
+async fn within<T>(
+    name: &str,
+    hook: &str,
+    timeout: Option<Duration>,
+    future: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    let future = future.instrument(tracing::info_span!("adapter", adapter = name, hook));
+    let Some(timeout) = timeout else {
+        return future.await;
+    };
+    tokio::time::timeout(timeout, future)
+        .await
+        .unwrap_or_else(|_| {
+            Err(errors::Error::Message(format!(
+                "adapter `{name}` {hook} timed out after {timeout:?}"
+            )))
+        })
+}
+
+/// Initialize the adapter, retrying following its policy.
+async fn init_adapter(adapter: &mut dyn Adapter, policy: &AdapterPolicy) -> Result<()> {
+    let name = adapter.name();
+    let mut attempt = 0;
+    loop {
+        let result = match within(&name, "init", policy.timeouts.init, adapter.init()).await {
+            Ok(()) => return Ok(()),
+            Err(e) => adapter.handle_error(Box::new(e)).await,
+        };
+        let Err(e) = result else {
+            return Ok(());
+        };
+        match &policy.init_retry {
+            Some(backoff) if backoff.max_retries.is_none_or(|max| attempt < max) => {
+                let delay = backoff.delay(attempt);
+                attempt += 1;
+                tracing::warn!(adapter = name, attempt, ?delay, error = %e, "adapter init failed, retry");
+                tokio::time::sleep(delay).await;
+            }
+            _ => return Err(e),
+        }
+    }
+}
+
+/// Value of `adapters.<key>.enable`, adapters are enabled by default.
+fn adapter_enabled(key: &str, enable: Option<serde_json::Value>) -> Result<bool> {
+    match enable {
+        None | Some(serde_json::Value::Null) => Ok(true),
+        Some(serde_json::Value::Bool(enable)) => Ok(enable),
+        // Environment variable overrides are strings.
+        Some(serde_json::Value::String(enable)) if enable == "true" => Ok(true),
+        Some(serde_json::Value::String(enable)) if enable == "false" => Ok(false),
+        Some(other) => Err(errors::Error::Message(format!(
+            "invalid configuration `adapters.{key}.enable`: expected a boolean, found {other}"
+        ))),
+    }
+}
+
+/// Dependency level of each adapter: `0` without dependency, otherwise one
+/// more than its deepest dependency.
+fn dependency_levels(adapters: &[Box<dyn Adapter>]) -> Result<Vec<usize>> {
+    let mut indexes = HashMap::new();
+    for (i, adapter) in adapters.iter().enumerate() {
+        if indexes.insert(adapter.name(), i).is_some() {
+            return Err(errors::Error::Message(format!(
+                "adapter `{}` is registered twice",
+                adapter.name()
+            )));
+        }
+    }
+
+    let mut dependencies = Vec::with_capacity(adapters.len());
+    for adapter in adapters {
+        let mut deps = vec![];
+        for name in adapter.depends_on() {
+            let Some(&i) = indexes.get(&name) else {
+                return Err(errors::Error::Message(format!(
+                    "adapter `{}` depends on `{name}` which is not registered",
+                    adapter.name()
+                )));
+            };
+            deps.push(i);
+        }
+        dependencies.push(deps);
+    }
+
+    fn visit(
+        i: usize,
+        dependencies: &[Vec<usize>],
+        levels: &mut [Option<usize>],
+        path: &mut Vec<usize>,
+        adapters: &[Box<dyn Adapter>],
+    ) -> Result<usize> {
+        if let Some(level) = levels[i] {
+            return Ok(level);
+        }
+        if let Some(start) = path.iter().position(|&p| p == i) {
+            let cycle: Vec<_> = path[start..]
+                .iter()
+                .chain(std::iter::once(&i))
+                .map(|&p| adapters[p].name())
+                .collect();
+            return Err(errors::Error::Message(format!(
+                "adapter dependency cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        path.push(i);
+        let mut level = 0;
+        for &dep in &dependencies[i] {
+            level = level.max(visit(dep, dependencies, levels, path, adapters)? + 1);
+        }
+        path.pop();
+        levels[i] = Some(level);
+        Ok(level)
+    }
+
+    let mut levels = vec![None; adapters.len()];
+    for i in 0..adapters.len() {
+        visit(i, &dependencies, &mut levels, &mut vec![], adapters)?;
+    }
+    Ok(levels.into_iter().flatten().collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::atomic::{AtomicBool, Ordering};
-    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::Barrier;
 
     #[derive(Debug)]
     struct MockAdapter {
         name: String,
-        state: AdapterState,
         priority: AdapterPriority,
+        policy: AdapterPolicy,
+        fail_on: Option<&'static str>,
+        init_failures: Arc<AtomicU32>,
+        depends_on: Vec<String>,
+        init_barrier: Option<Arc<Barrier>>,
+        events: Arc<Mutex<Vec<String>>>,
         init_called: Arc<AtomicBool>,
         before_run_called: Arc<AtomicBool>,
         after_route_called: Arc<AtomicBool>,
@@ -186,13 +1098,35 @@ mod tests {
         fn new(name: &str, priority: AdapterPriority) -> Self {
             Self {
                 name: name.to_string(),
-                state: AdapterState::Initialized,
                 priority,
+                policy: AdapterPolicy::default(),
+                fail_on: None,
+                init_failures: Arc::default(),
+                depends_on: vec![],
+                init_barrier: None,
+                events: Arc::default(),
                 init_called: Arc::new(AtomicBool::new(false)),
                 before_run_called: Arc::new(AtomicBool::new(false)),
                 after_route_called: Arc::new(AtomicBool::new(false)),
             }
         }
+
+        fn depends_on(mut self, names: &[&str]) -> Self {
+            self.depends_on = names.iter().map(|n| n.to_string()).collect();
+            self
+        }
+
+        fn events(mut self, events: &Arc<Mutex<Vec<String>>>) -> Self {
+            self.events = events.clone();
+            self
+        }
+
+        fn record(&self, event: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}:{event}", self.name));
+        }
     }
 
     #[async_trait]
@@ -205,17 +1139,50 @@ mod tests {
             self.priority.clone()
         }
 
-        fn state(&self) -> AdapterState {
-            self.state.clone()
+        fn depends_on(&self) -> Vec<String> {
+            self.depends_on.clone()
+        }
+
+        fn policy(&self) -> AdapterPolicy {
+            self.policy.clone()
+        }
+
+        async fn health(&self, _ctx: &Context) -> Result<()> {
+            match self.fail_on {
+                Some("health") => Err(errors::Error::string("pool closed")),
+                Some("health_timeout") => {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
         }
 
         async fn init(&mut self) -> Result<()> {
+            if let Some(barrier) = &self.init_barrier {
+                barrier.wait().await;
+            }
+            if self.fail_on == Some("init")
+                || self
+                    .init_failures
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok()
+            {
+                return Err(errors::Error::string("connection refused"));
+            }
+            if self.fail_on == Some("init_timeout") {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            self.record("init");
             self.init_called.store(true, Ordering::SeqCst);
             Ok(())
         }
 
         async fn before_run(&mut self, ctx: Context) -> Result<Context> {
             self.before_run_called.store(true, Ordering::SeqCst);
+            if self.fail_on == Some("before_run") {
+                return Err(errors::Error::string("migration failed"));
+            }
             Ok(ctx)
         }
 
@@ -223,6 +1190,14 @@ mod tests {
             self.after_route_called.store(true, Ordering::SeqCst);
             Ok(router)
         }
+
+        async fn before_stop(&self, _ctx: &Context) -> Result<()> {
+            self.record("stop");
+            if self.fail_on == Some("before_stop") {
+                return Err(errors::Error::string("flush failed"));
+            }
+            Ok(())
+        }
     }
 
     #[tokio::test]
@@ -328,4 +1303,495 @@ mod tests {
 
         assert!(rx.recv().await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_dependency_order() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut manager = AdapterManager::new(Context::default());
+        manager.register(Box::new(
+            MockAdapter::new("cache", AdapterPriority::High)
+                .depends_on(&["db"])
+                .events(&events),
+        ));
+        manager.register(Box::new(
+            MockAdapter::new("db", AdapterPriority::Low).events(&events),
+        ));
+        manager.register(Box::new(
+            MockAdapter::new("metrics", AdapterPriority::Normal).events(&events),
+        ));
+
+        manager.init_all().await.unwrap();
+        let names: Vec<_> = manager.adapters().iter().map(|a| a.name()).collect();
+        assert_eq!(names, ["metrics", "db", "cache"]);
+
+        manager.stop_all().await.unwrap();
+        let events = events.lock().unwrap().clone();
+        let position = |event: &str| events.iter().position(|e| e == event).unwrap();
+        assert!(position("db:init") < position("cache:init"));
+        assert!(position("cache:stop") < position("db:stop"));
+    }
+
+    #[tokio::test]
+    async fn test_dependency_errors() {
+        let mut manager = AdapterManager::new(Context::default());
+        manager.register(Box::new(
+            MockAdapter::new("cache", AdapterPriority::Normal).depends_on(&["db"]),
+        ));
+        let err = manager.init_all().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "adapter `cache` depends on `db` which is not registered"
+        );
+
+        manager.register(Box::new(
+            MockAdapter::new("db", AdapterPriority::Normal).depends_on(&["cache"]),
+        ));
+        let err = manager.init_all().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "adapter dependency cycle: cache -> db -> cache"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_independent_adapters_init_concurrently() {
+        let barrier = Arc::new(Barrier::new(2));
+        let mut manager = AdapterManager::new(Context::default());
+        for name in ["a", "b"] {
+            let mut adapter = MockAdapter::new(name, AdapterPriority::Normal);
+            adapter.init_barrier = Some(barrier.clone());
+            manager.register(Box::new(adapter));
+        }
+
+        tokio::time::timeout(Duration::from_secs(1), manager.init_all())
+            .await
+            .expect("adapters were initialized sequentially")
+            .unwrap();
+        assert_eq!(manager.adapters().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_adapter_status() {
+        let mut manager = AdapterManager::new(Context::default());
+        manager.register(Box::new(
+            MockAdapter::new("cache", AdapterPriority::Normal).depends_on(&["db"]),
+        ));
+        manager.register(Box::new(MockAdapter::new("db", AdapterPriority::Normal)));
+        let states = |manager: &AdapterManager| {
+            manager
+                .status()
+                .into_iter()
+                .map(|s| (s.name, s.state))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            states(&manager),
+            [
+                ("cache".to_string(), AdapterState::Registered),
+                ("db".to_string(), AdapterState::Registered)
+            ]
+        );
+
+        manager.init_all().await.unwrap();
+        assert_eq!(
+            states(&manager),
+            [
+                ("db".to_string(), AdapterState::Initialized),
+                ("cache".to_string(), AdapterState::Initialized)
+            ]
+        );
+
+        let ctx = manager.before_run().await.unwrap();
+        let statuses = ctx.get::<AdapterStatuses>().unwrap();
+        assert!(statuses
+            .snapshot()
+            .iter()
+            .all(|s| s.state == AdapterState::Running));
+
+        manager.stop_all().await.unwrap();
+        assert_eq!(statuses.get("db").unwrap().state, AdapterState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_adapter_failure_status() {
+        let mut manager = AdapterManager::new(Context::default());
+        let mut db = MockAdapter::new("db", AdapterPriority::Normal);
+        db.fail_on = Some("init");
+        manager.register(Box::new(db));
+        manager.register(Box::new(
+            MockAdapter::new("cache", AdapterPriority::Normal).depends_on(&["db"]),
+        ));
+
+        assert!(manager.init_all().await.is_err());
+        let db = manager.statuses().get("db").unwrap();
+        assert_eq!(db.state, AdapterState::Failed);
+        assert_eq!(db.error.as_deref(), Some("init: connection refused"));
+        // Dependents are not initialized once a dependency failed.
+        let cache = manager.statuses().get("cache").unwrap();
+        assert_eq!(cache.state, AdapterState::Registered);
+
+        let mut manager = AdapterManager::new(Context::default());
+        let mut db = MockAdapter::new("db", AdapterPriority::Normal);
+        db.fail_on = Some("before_run");
+        manager.register(Box::new(db));
+        manager.init_all().await.unwrap();
+        assert!(manager.before_run().await.is_err());
+        let db = manager.statuses().get("db").unwrap();
+        assert_eq!(db.state, AdapterState::Failed);
+        assert_eq!(db.error.as_deref(), Some("before_run: migration failed"));
+    }
+
+    #[tokio::test]
+    async fn test_check_health() {
+        let mut configs =
+            crate::config::load_configuration(&crate::config::Environment::Development).unwrap();
+        configs.server.health.timeout = 10;
+        let ctx = Context {
+            configs: Some(configs),
+            ..Context::default()
+        };
+
+        let mut manager = AdapterManager::new(ctx.clone());
+        let mut cache = MockAdapter::new("cache", AdapterPriority::Normal);
+        cache.policy = AdapterPolicy::optional();
+        cache.fail_on = Some("health");
+        manager.register(Box::new(cache));
+        manager.register(Box::new(MockAdapter::new("db", AdapterPriority::Normal)));
+        assert!(manager.check_health().await);
+        let cache = manager.statuses().get("cache").unwrap();
+        let health = cache.health.unwrap();
+        assert!(!health.healthy);
+        assert_eq!(health.error.as_deref(), Some("pool closed"));
+
+        let mut manager = AdapterManager::new(ctx);
+        let mut db = MockAdapter::new("db", AdapterPriority::Normal);
+        db.fail_on = Some("health_timeout");
+        manager.register(Box::new(db));
+        assert!(!manager.check_health().await);
+        assert!(!manager.statuses().is_ready());
+        let db = manager.statuses().get("db").unwrap();
+        assert!(db
+            .health
+            .unwrap()
+            .error
+            .unwrap()
+            .starts_with("health check timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_readyz_error_details() {
+        use crate::health::{readyz, ErrorDetails};
+        use axum::Extension;
+
+        let mut manager = AdapterManager::new(Context::default());
+        let mut cache = MockAdapter::new("cache", AdapterPriority::Normal);
+        cache.policy = AdapterPolicy::optional();
+        cache.fail_on = Some("health");
+        manager.register(Box::new(cache));
+        assert!(manager.check_health().await);
+
+        let body = |details: Option<bool>| {
+            let statuses = manager.statuses();
+            async move {
+                let response = readyz(
+                    None,
+                    Some(Extension(statuses)),
+                    details.map(|details| Extension(ErrorDetails(details))),
+                )
+                .await
+                .unwrap();
+                assert_eq!(response.status(), http::StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        let public = body(None).await;
+        assert_eq!(
+            public,
+            serde_json::json!({
+                "ok": true,
+                "adapters": [{ "name": "cache", "state": "registered", "required": false, "healthy": false }]
+            })
+        );
+        assert_eq!(body(Some(false)).await, public);
+        assert_eq!(
+            body(Some(true)).await["adapters"][0]["error"],
+            "pool closed"
+        );
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct PoolConfig {
+        url: String,
+        #[serde(default)]
+        max_connections: u32,
+    }
+
+    #[derive(Debug)]
+    struct PoolAdapter {
+        key: &'static str,
+        config: Arc<Mutex<Option<(String, u32)>>>,
+    }
+
+    #[async_trait]
+    impl Adapter for PoolAdapter {
+        fn name(&self) -> String {
+            format!("pool-{}", self.key)
+        }
+
+        fn config_key(&self) -> String {
+            self.key.to_string()
+        }
+
+        fn configure(&mut self, config: AdapterConfig<'_>) -> Result<()> {
+            let config: PoolConfig = config.bind()?;
+            *self.config.lock().unwrap() = Some((config.url, config.max_connections));
+            Ok(())
+        }
+    }
+
+    fn adapters_context(adapters: serde_json::Value) -> Context {
+        let mut configs =
+            crate::config::load_configuration(&crate::config::Environment::Development).unwrap();
+        configs.adapters = serde_json::from_value(adapters).unwrap();
+        Context {
+            configs: Some(configs),
+            ..Context::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_configure_adapters() {
+        let ctx = adapters_context(serde_json::json!({
+            "db": { "url": "postgres://localhost/app", "max_connections": 8 },
+            "cache": { "enable": false, "url": "redis://localhost" },
+        }));
+        let db = Arc::new(Mutex::new(None));
+        let cache = Arc::new(Mutex::new(None));
+        let mut manager = AdapterManager::new(ctx.clone());
+        manager.register(Box::new(PoolAdapter {
+            key: "db",
+            config: db.clone(),
+        }));
+        manager.register(Box::new(PoolAdapter {
+            key: "cache",
+            config: cache.clone(),
+        }));
+        manager.init_all().await.unwrap();
+
+        assert_eq!(
+            db.lock().unwrap().clone(),
+            Some(("postgres://localhost/app".to_string(), 8))
+        );
+        assert!(cache.lock().unwrap().is_none());
+        let names: Vec<_> = manager.adapters().iter().map(|a| a.name()).collect();
+        assert_eq!(names, ["pool-db"]);
+        assert_eq!(
+            manager.statuses().get("pool-cache").unwrap().state,
+            AdapterState::Disabled
+        );
+
+        // A dependency on a disabled adapter fails the startup.
+        let mut manager = AdapterManager::new(ctx);
+        manager.register(Box::new(PoolAdapter {
+            key: "cache",
+            config: Arc::default(),
+        }));
+        manager.register(Box::new(
+            MockAdapter::new("session", AdapterPriority::Normal).depends_on(&["pool-cache"]),
+        ));
+        assert_eq!(
+            manager.init_all().await.unwrap_err().to_string(),
+            "adapter `session` depends on `pool-cache` which is disabled"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_configure_errors() {
+        let errors = [
+            (
+                serde_json::json!({ "db": { "url": "postgres://", "max_connections": "ten" } }),
+                "invalid configuration `adapters.db.max_connections`: invalid type: string \"ten\", expected u32",
+            ),
+            (
+                serde_json::json!({}),
+                "invalid configuration `adapters.db`: missing field `url`",
+            ),
+            (
+                serde_json::json!({ "db": { "enable": "maybe" } }),
+                "invalid configuration `adapters.db.enable`: expected a boolean, found \"maybe\"",
+            ),
+        ];
+        for (adapters, expected) in errors {
+            let mut manager = AdapterManager::new(adapters_context(adapters));
+            manager.register(Box::new(PoolAdapter {
+                key: "db",
+                config: Arc::default(),
+            }));
+            let err = manager.init_all().await.unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_optional_adapter_failures_are_skipped() {
+        let mut manager = AdapterManager::new(Context::default());
+        let mut cache = MockAdapter::new("cache", AdapterPriority::Normal);
+        cache.fail_on = Some("init");
+        cache.policy = AdapterPolicy::optional();
+        manager.register(Box::new(cache));
+        let mut session =
+            MockAdapter::new("session", AdapterPriority::Normal).depends_on(&["cache"]);
+        session.policy = AdapterPolicy::optional();
+        manager.register(Box::new(session));
+        let mut search = MockAdapter::new("search", AdapterPriority::Normal);
+        search.fail_on = Some("before_run");
+        search.policy = AdapterPolicy::optional();
+        manager.register(Box::new(search));
+        manager.register(Box::new(MockAdapter::new("db", AdapterPriority::Normal)));
+
+        manager.init_all().await.unwrap();
+        manager.before_run().await.unwrap();
+        let names: Vec<_> = manager.adapters().iter().map(|a| a.name()).collect();
+        assert_eq!(names, ["db"]);
+        let state = |name: &str| manager.statuses().get(name).unwrap();
+        assert_eq!(state("cache").state, AdapterState::Failed);
+        assert_eq!(state("session").state, AdapterState::Skipped);
+        assert_eq!(
+            state("session").error.as_deref(),
+            Some("dependency `cache` failed")
+        );
+        assert_eq!(state("search").state, AdapterState::Failed);
+        assert_eq!(state("db").state, AdapterState::Running);
+
+        // A required adapter depending on a failed optional one stops the startup.
+        let mut manager = AdapterManager::new(Context::default());
+        let mut cache = MockAdapter::new("cache", AdapterPriority::Normal);
+        cache.fail_on = Some("init");
+        cache.policy = AdapterPolicy::optional();
+        manager.register(Box::new(cache));
+        manager.register(Box::new(
+            MockAdapter::new("session", AdapterPriority::Normal).depends_on(&["cache"]),
+        ));
+        assert_eq!(
+            manager.init_all().await.unwrap_err().to_string(),
+            "adapter `session` depends on `cache` which failed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_init_retry_and_timeout() {
+        let mut manager = AdapterManager::new(Context::default());
+        let mut db = MockAdapter::new("db", AdapterPriority::Normal);
+        db.init_failures = Arc::new(AtomicU32::new(2));
+        db.policy.init_retry = Some(Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(5),
+            max_retries: Some(2),
+        });
+        let init_called = db.init_called.clone();
+        manager.register(Box::new(db));
+        manager.init_all().await.unwrap();
+        assert!(init_called.load(Ordering::SeqCst));
+
+        let mut manager = AdapterManager::new(Context::default());
+        let mut db = MockAdapter::new("db", AdapterPriority::Normal);
+        db.init_failures = Arc::new(AtomicU32::new(2));
+        db.policy.init_retry = Some(Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(5),
+            max_retries: Some(1),
+        });
+        manager.register(Box::new(db));
+        assert_eq!(
+            manager.init_all().await.unwrap_err().to_string(),
+            "connection refused"
+        );
+
+        let mut manager = AdapterManager::new(Context::default());
+        let mut db = MockAdapter::new("db", AdapterPriority::Normal);
+        db.fail_on = Some("init_timeout");
+        db.policy.timeouts.init = Some(Duration::from_millis(10));
+        manager.register(Box::new(db));
+        assert_eq!(
+            manager.init_all().await.unwrap_err().to_string(),
+            "adapter `db` init timed out after 10ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_all_continues_after_failure() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut manager = AdapterManager::new(Context::default());
+        manager.register(Box::new(
+            MockAdapter::new("db", AdapterPriority::Normal).events(&events),
+        ));
+        let mut cache = MockAdapter::new("cache", AdapterPriority::Normal)
+            .depends_on(&["db"])
+            .events(&events);
+        cache.fail_on = Some("before_stop");
+        manager.register(Box::new(cache));
+        manager.init_all().await.unwrap();
+
+        assert_eq!(
+            manager.stop_all().await.unwrap_err().to_string(),
+            "flush failed"
+        );
+        assert!(events.lock().unwrap().contains(&"db:stop".to_string()));
+        assert_eq!(
+            manager.statuses().get("cache").unwrap().error.as_deref(),
+            Some("before_stop: flush failed")
+        );
+        assert_eq!(
+            manager.statuses().get("db").unwrap().state,
+            AdapterState::Stopped
+        );
+    }
+
+    #[derive(Debug)]
+    struct DocAdapter(&'static str);
+
+    #[async_trait]
+    impl Adapter for DocAdapter {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn openapi(&self) -> Option<OpenApi> {
+            use utoipa::OpenApi;
+
+            #[derive(OpenApi)]
+            #[openapi(paths(crate::health::healthz))]
+            struct HealthDoc;
+
+            #[derive(OpenApi)]
+            #[openapi(paths(crate::health::adapters))]
+            struct AdaptersDoc;
+
+            match self.0 {
+                "health" => Some(HealthDoc::openapi()),
+                "adapters" => Some(AdaptersDoc::openapi()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_adapters_openapi() {
+        let mut manager = AdapterManager::new(Context::default());
+        manager.register(Box::new(DocAdapter("none")));
+        assert!(manager.openapi().is_none());
+
+        manager.register(Box::new(DocAdapter("health")));
+        manager.register(Box::new(DocAdapter("adapters")));
+        let doc = manager.openapi().unwrap();
+        let paths: Vec<_> = doc.paths.paths.keys().cloned().collect();
+        assert_eq!(paths, ["/adapters", "/healthz"]);
+        let schemas = doc.components.unwrap().schemas;
+        assert!(schemas.contains_key("AdapterStatus"));
+    }
 }
@@ -0,0 +1,203 @@
+//! In-memory LRU cache, for development and tests.
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use super::{Cache, CacheStats, Counters};
+use crate::Result;
+
+#[derive(Debug)]
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Option<Instant>,
+    used: u64,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// Entries with their last use, the least recently used is evicted first.
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<String, Entry>,
+    order: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+impl Lru {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Live entry of `key` marked as used, an expired one is removed.
+    fn touch(&mut self, key: &str) -> Option<&Entry> {
+        let now = Instant::now();
+        if self.entries.get(key)?.is_expired(now) {
+            self.remove(key);
+            return None;
+        }
+        let used = self.tick();
+        let entry = self.entries.get_mut(key)?;
+        self.order.remove(&entry.used);
+        self.order.insert(used, key.to_string());
+        entry.used = used;
+        Some(entry)
+    }
+
+    fn insert(&mut self, key: &str, value: Vec<u8>, ttl: Option<Duration>, capacity: usize) {
+        self.remove(key);
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        let used = self.tick();
+        self.order.insert(used, key.to_string());
+        self.entries.insert(
+            key.to_string(),
+            Entry {
+                value,
+                expires_at: ttl.map(|ttl| Instant::now() + ttl),
+                used,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.used);
+        Some(entry)
+    }
+}
+
+/// Cache keeping at most `capacity` entries in memory.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    lru: Mutex<Lru>,
+    counters: Counters,
+}
+
+impl MemoryCache {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            lru: Mutex::default(),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Number of entries, expired ones included until they are looked up or
+    /// evicted
+    pub fn len(&self) -> usize {
+        self.lru().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lru(&self) -> std::sync::MutexGuard<'_, Lru> {
+        self.lru.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value = self.lru().touch(key).map(|entry| entry.value.clone());
+        self.counters.record(&value);
+        Ok(value)
+    }
+
+    async fn set_raw(&self, key: &str, value: Vec<u8>, ttl: Option<Duration>) -> Result<()> {
+        self.lru().insert(key, value, ttl, self.capacity);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool> {
+        let now = Instant::now();
+        Ok(self
+            .lru()
+            .remove(key)
+            .is_some_and(|entry| !entry.is_expired(now)))
+    }
+
+    async fn ttl(&self, key: &str) -> Result<Option<Duration>> {
+        let now = Instant::now();
+        let lru = self.lru();
+        Ok(lru
+            .entries
+            .get(key)
+            .and_then(|entry| entry.expires_at)
+            .and_then(|at| at.checked_duration_since(now))
+            .filter(|ttl| !ttl.is_zero()))
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.counters.stats()
+    }
+
+    async fn close(&self) -> Result<()> {
+        *self.lru() = Lru::default();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.set_raw("a", b"1".to_vec(), None).await.unwrap();
+        cache.set_raw("b", b"2".to_vec(), None).await.unwrap();
+        // `a` becomes the most recently used, `b` is evicted.
+        assert!(cache.get_raw("a").await.unwrap().is_some());
+        cache.set_raw("c", b"3".to_vec(), None).await.unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_raw("b").await.unwrap(), None);
+        assert_eq!(cache.get_raw("a").await.unwrap(), Some(b"1".to_vec()));
+        assert_eq!(cache.get_raw("c").await.unwrap(), Some(b"3".to_vec()));
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 1 });
+
+        // Overwriting a key does not evict another one.
+        cache.set_raw("c", b"4".to_vec(), None).await.unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_raw("c").await.unwrap(), Some(b"4".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_expiration() {
+        let cache = MemoryCache::new(10);
+        cache
+            .set_raw("session", b"1".to_vec(), Some(Duration::from_millis(20)))
+            .await
+            .unwrap();
+        cache.set_raw("config", b"2".to_vec(), None).await.unwrap();
+
+        let ttl = cache.ttl("session").await.unwrap().unwrap();
+        assert!(ttl <= Duration::from_millis(20));
+        assert_eq!(cache.ttl("config").await.unwrap(), None);
+        assert_eq!(cache.ttl("missing").await.unwrap(), None);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(cache.ttl("session").await.unwrap(), None);
+        assert_eq!(cache.get_raw("session").await.unwrap(), None);
+        assert!(!cache.delete("session").await.unwrap());
+        assert!(cache.delete("config").await.unwrap());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+//! Cache shared by the handlers.
+//!
+//! [`CacheAdapter`] builds the backend selected by `adapters.cache.backend`,
+//! an in-memory LRU or a Redis server (`redis` cargo feature), and puts a
+//! [`SharedCache`] into the context.
+//!
+//! Example
+//! ```rust,ignore
+//! async fn user(State(ctx): State<Context>, Path(id): Path<u64>) -> Result<Json<User>> {
+//!     let cache = ctx.get::<SharedCache>().expect("cache adapter is registered");
+//!     let key = format!("user:{id}");
+//!     if let Some(user) = cache.get::<User>(&key).await? {
+//!         return Ok(Json(user));
+//!     }
+//!     let user = load_user(id).await?;
+//!     cache.set(&key, &user, Some(Duration::from_secs(60))).await?;
+//!     Ok(Json(user))
+//! }
+//! ```
+pub mod memory;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+use std::{
+    fmt::Debug,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    adapter::{Adapter, AdapterConfig},
+    context::Context,
+    errors::Error,
+    secret::Secret,
+    Result,
+};
+
+/// Storage of the cached values, serialized by the typed methods of
+/// `dyn Cache`.
+#[async_trait]
+pub trait Cache: Send + Sync + Debug {
+    /// Value of `key`, `None` when it is missing or expired
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store `value`, it expires after `ttl` when given
+    async fn set_raw(&self, key: &str, value: Vec<u8>, ttl: Option<Duration>) -> Result<()>;
+
+    /// Remove `key`, returns whether it was present
+    async fn delete(&self, key: &str) -> Result<bool>;
+
+    /// Remaining time to live of `key`, `None` when it is missing or never
+    /// expires
+    async fn ttl(&self, key: &str) -> Result<Option<Duration>>;
+
+    /// Hits and misses of the lookups so far
+    fn stats(&self) -> CacheStats;
+
+    /// Check that the backend is reachable
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Release the connections of the backend
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl dyn Cache {
+    /// Deserialized value of `key`
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.get_raw(key)
+            .await?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()
+            .map_err(Error::from)
+    }
+
+    /// Serialize and store `value`, it expires after `ttl` when given
+    pub async fn set<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> Result<()> {
+        self.set_raw(key, serde_json::to_vec(value)?, ttl).await
+    }
+}
+
+/// Hits and misses of a cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Lookup counters shared by the backends.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn record<T>(&self, value: &Option<T>) {
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Cache set in the context by the [`CacheAdapter`].
+#[derive(Debug, Clone)]
+pub struct SharedCache(pub Arc<dyn Cache>);
+
+impl Deref for SharedCache {
+    type Target = dyn Cache;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// Backend of the [`CacheAdapter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    #[default]
+    Memory,
+    Redis,
+}
+
+/// Configuration of the [`CacheAdapter`], bound from `adapters.cache`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub backend: CacheBackend,
+    /// Maximum number of entries of the memory backend
+    pub capacity: usize,
+    /// URL of the Redis server, `redis://` or `rediss://`, redacted when
+    /// printed as it may hold the password
+    pub url: Option<Secret<String>>,
+    /// Maximum time in milliseconds to connect to the Redis server
+    pub connect_timeout: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            backend: CacheBackend::Memory,
+            capacity: 10_000,
+            url: None,
+            connect_timeout: 5000,
+        }
+    }
+}
+
+/// Adapter connecting the cache backend in `init` and closing it in
+/// `before_stop`, the backend reachability is reported to `/readyz`.
+#[derive(Debug, Default)]
+pub struct CacheAdapter {
+    config: CacheConfig,
+    cache: Option<Arc<dyn Cache>>,
+}
+
+impl CacheAdapter {
+    fn connected(&self) -> Result<&Arc<dyn Cache>> {
+        self.cache
+            .as_ref()
+            .ok_or_else(|| Error::string("cache is not connected"))
+    }
+}
+
+#[async_trait]
+impl Adapter for CacheAdapter {
+    fn name(&self) -> String {
+        "cache".to_string()
+    }
+
+    fn configure(&mut self, config: AdapterConfig<'_>) -> Result<()> {
+        self.config = config.bind()?;
+        if self.config.backend == CacheBackend::Redis && self.config.url.is_none() {
+            return Err(Error::Message(format!(
+                "missing configuration `adapters.{}.url`",
+                config.key()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn init(&mut self) -> Result<()> {
+        let cache: Arc<dyn Cache> = match self.config.backend {
+            CacheBackend::Memory => Arc::new(memory::MemoryCache::new(self.config.capacity)),
+            #[cfg(feature = "redis")]
+            CacheBackend::Redis => {
+                let url = self.config.url.as_ref().map_or("", |url| url.expose());
+                let timeout = Duration::from_millis(self.config.connect_timeout);
+                Arc::new(redis::RedisCache::connect(url, timeout).await?)
+            }
+            #[cfg(not(feature = "redis"))]
+            CacheBackend::Redis => {
+                return Err(Error::string(
+                    "the redis cache backend requires the `redis` feature of ymir",
+                ))
+            }
+        };
+        self.cache = Some(cache);
+        Ok(())
+    }
+
+    async fn before_run(&mut self, mut ctx: Context) -> Result<Context> {
+        ctx.set(SharedCache(self.connected()?.clone()));
+        Ok(ctx)
+    }
+
+    async fn health(&self, _ctx: &Context) -> Result<()> {
+        self.connected()?.ping().await
+    }
+
+    async fn before_stop(&self, _ctx: &Context) -> Result<()> {
+        if let Some(cache) = &self.cache {
+            let stats = cache.stats();
+            tracing::info!(hits = stats.hits, misses = stats.misses, "closing cache");
+            cache.close().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AdapterManager;
+
+    fn cache_context(cache: serde_json::Value) -> Context {
+        crate::testing::context_with(|cfg| {
+            cfg.adapters = serde_json::from_value(serde_json::json!({ "cache": cache })).unwrap();
+        })
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: u64,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_cache_adapter() {
+        let mut manager = AdapterManager::new(cache_context(serde_json::json!({ "capacity": 2 })));
+        manager.register(Box::new(CacheAdapter::default()));
+        manager.init_all().await.unwrap();
+        let ctx = manager.before_run().await.unwrap();
+        let cache = ctx.get::<SharedCache>().unwrap().clone();
+
+        let user = User {
+            id: 1,
+            name: "ymir".to_string(),
+        };
+        cache.set("user:1", &user, None).await.unwrap();
+        assert_eq!(cache.get::<User>("user:1").await.unwrap(), Some(user));
+        assert_eq!(cache.get::<User>("user:2").await.unwrap(), None);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+        assert!(cache.get::<u64>("user:1").await.is_err());
+
+        assert!(cache.delete("user:1").await.unwrap());
+        assert!(!cache.delete("user:1").await.unwrap());
+        assert!(manager.check_health().await);
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cache_configuration_errors() {
+        let mut manager =
+            AdapterManager::new(cache_context(serde_json::json!({ "backend": "redis" })));
+        manager.register(Box::new(CacheAdapter::default()));
+        assert_eq!(
+            manager.init_all().await.unwrap_err().to_string(),
+            "missing configuration `adapters.cache.url`"
+        );
+
+        let mut manager =
+            AdapterManager::new(cache_context(serde_json::json!({ "backend": "disk" })));
+        manager.register(Box::new(CacheAdapter::default()));
+        assert!(manager
+            .init_all()
+            .await
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid configuration `adapters.cache.backend`"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Cache stored on a server speaking the Redis protocol.
+use std::{
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use redis::{
+    aio::{ConnectionManager, ConnectionManagerConfig},
+    AsyncCommands, Client, SetExpiry, SetOptions,
+};
+
+use super::{Cache, CacheStats, Counters};
+use crate::{errors::Error, secret::redact_url, Result};
+
+/// Cache backed by a Redis server, the connection is re-established when it
+/// is lost.
+pub struct RedisCache {
+    conn: Mutex<Option<ConnectionManager>>,
+    counters: Counters,
+}
+
+impl std::fmt::Debug for RedisCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisCache")
+            .field("counters", &self.counters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisCache {
+    /// Connect to the server at `url`, `redis://host:port/db`, within
+    /// `timeout`. The connection is not retried, [`crate::adapter::AdapterPolicy`]
+    /// retries the adapter `init`.
+    pub async fn connect(url: &str, timeout: Duration) -> Result<Self> {
+        let client = Client::open(url)
+            .map_err(|e| Error::Message(format!("invalid redis url `{}`: {e}", redact_url(url))))?;
+        let config = ConnectionManagerConfig::new()
+            .set_number_of_retries(0)
+            .set_connection_timeout(timeout);
+        let conn = ConnectionManager::new_with_config(client, config)
+            .await
+            .map_err(|e| Error::Message(format!("failed to connect to redis: {e}")))?;
+        Ok(Self {
+            conn: Mutex::new(Some(conn)),
+            counters: Counters::default(),
+        })
+    }
+
+    fn conn(&self) -> Result<ConnectionManager> {
+        self.conn
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| Error::string("redis cache is closed"))
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value: Option<Vec<u8>> = self.conn()?.get(key).await.map_err(Error::wrap)?;
+        self.counters.record(&value);
+        Ok(value)
+    }
+
+    async fn set_raw(&self, key: &str, value: Vec<u8>, ttl: Option<Duration>) -> Result<()> {
+        let mut conn = self.conn()?;
+        match ttl {
+            Some(ttl) => {
+                let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+                let options = SetOptions::default().with_expiration(SetExpiry::PX(millis));
+                conn.set_options::<_, _, ()>(key, value, options).await
+            }
+            None => conn.set::<_, _, ()>(key, value).await,
+        }
+        .map_err(Error::wrap)
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool> {
+        let removed: u64 = self.conn()?.del(key).await.map_err(Error::wrap)?;
+        Ok(removed > 0)
+    }
+
+    async fn ttl(&self, key: &str) -> Result<Option<Duration>> {
+        // -2 when the key is missing, -1 when it never expires.
+        let millis: i64 = self.conn()?.pttl(key).await.map_err(Error::wrap)?;
+        Ok(u64::try_from(millis).ok().map(Duration::from_millis))
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.counters.stats()
+    }
+
+    async fn ping(&self) -> Result<()> {
+        redis::cmd("PING")
+            .query_async::<()>(&mut self.conn()?)
+            .await
+            .map_err(Error::wrap)
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.conn
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connect_errors() {
+        assert!(
+            RedisCache::connect("http://localhost", Duration::from_secs(1))
+                .await
+                .unwrap_err()
+                .to_string()
+                .starts_with("invalid redis url `http://localhost`")
+        );
+        assert!(
+            RedisCache::connect("redis://127.0.0.1:1", Duration::from_secs(1))
+                .await
+                .unwrap_err()
+                .to_string()
+                .starts_with("failed to connect to redis")
+        );
+    }
+}
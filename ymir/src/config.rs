@@ -1,7 +1,13 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 
+use crate::secret::{self, Secret};
+
 /// INTERCEPTIONS.
 /// CORS interception configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -83,20 +89,219 @@ pub struct Interceptions {
 
 // APPLICATION CONFIGURATIONS.
 
-/// Adapters configuration
+/// Graceful shutdown configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ServerShutdown {
+    /// Delay in milliseconds between the shutdown signal and closing the
+    /// listener. `/readyz` reports not ready during this window.
+    #[serde(default)]
+    pub pre_stop_delay: u64,
+    /// Maximum time in milliseconds to drain in-flight requests, remaining
+    /// connections are closed after it.
+    #[serde(default = "default_drain_timeout")]
+    pub drain_timeout: u64,
+}
+
+fn default_drain_timeout() -> u64 {
+    30_000
+}
+
+impl Default for ServerShutdown {
+    fn default() -> Self {
+        Self {
+            pre_stop_delay: 0,
+            drain_timeout: default_drain_timeout(),
+        }
+    }
+}
+
+/// Adapters health checks configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ServerHealth {
+    /// Interval in milliseconds between two checks, `0` only checks at
+    /// startup.
+    #[serde(default = "default_health_interval")]
+    pub interval: u64,
+    /// Maximum time in milliseconds of a check, an adapter is unhealthy
+    /// after it.
+    #[serde(default = "default_health_timeout")]
+    pub timeout: u64,
+}
+
+fn default_health_interval() -> u64 {
+    10_000
+}
+
+fn default_health_timeout() -> u64 {
+    2_000
+}
+
+impl Default for ServerHealth {
+    fn default() -> Self {
+        Self {
+            interval: default_health_interval(),
+            timeout: default_health_timeout(),
+        }
+    }
+}
+
+/// HTTP/1 settings of the connections.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ServerHttp1 {
+    /// Keep connections open between requests
+    #[serde(default = "default_true")]
+    pub keep_alive: bool,
+    /// Maximum time in milliseconds to receive the request headers, `None`
+    /// disables the timeout.
+    #[serde(default = "default_header_read_timeout")]
+    pub header_read_timeout: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_header_read_timeout() -> Option<u64> {
+    Some(30_000)
+}
+
+impl Default for ServerHttp1 {
+    fn default() -> Self {
+        Self {
+            keep_alive: true,
+            header_read_timeout: default_header_read_timeout(),
+        }
+    }
+}
+
+/// HTTP/2 settings of the connections, unset values use the hyper defaults.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ServerHttp2 {
+    /// Maximum concurrent streams per connection
+    pub max_concurrent_streams: Option<u32>,
+    /// Interval in milliseconds between keep-alive pings, `None` disables them
+    pub keep_alive_interval: Option<u64>,
+    /// Time in milliseconds to wait for a keep-alive ping acknowledgement
+    pub keep_alive_timeout: Option<u64>,
+    /// Initial flow control window size of a stream, in bytes
+    pub initial_stream_window_size: Option<u32>,
+    /// Initial flow control window size of a connection, in bytes
+    pub initial_connection_window_size: Option<u32>,
+    /// Maximum frame size, in bytes
+    pub max_frame_size: Option<u32>,
+}
+
+/// Connection level settings of the listeners.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ServerConnection {
+    /// Disable Nagle's algorithm on TCP connections
+    #[serde(default)]
+    pub tcp_nodelay: bool,
+    /// Maximum concurrent connections across every listener, new connections
+    /// wait in the listen backlog once it is reached.
+    pub max_connections: Option<usize>,
+    /// Accept HTTP/2 with prior knowledge on plain text connections (h2c),
+    /// they only speak HTTP/1 otherwise. TLS connections negotiate the
+    /// protocol with ALPN.
+    #[serde(default)]
+    pub h2c: bool,
+    #[serde(default)]
+    pub http1: ServerHttp1,
+    #[serde(default)]
+    pub http2: ServerHttp2,
+}
+
+/// Adapters configuration, keyed by [`Adapter::config_key`].
 ///
-/// Example (development): To configure settings for oauth2 or custom view
-/// engine
+/// Each section is given to [`Adapter::configure`] before `init`, and
+/// `enable: false` disables the adapter in the environment.
+///
+/// Example (development):
 /// ```yaml
-/// # config/development.yaml
+/// # configs/development.yaml
 /// adapters:
-///  oauth2:
-///   authorization_code: # Authorization code grant type
-///     - client_identifier: google # Identifier for the `OAuth2` provider.
-///       Replace 'google' with your provider's name if different, must be
-///       unique within the oauth2 config. ... # other fields
+///   database:
+///     url: postgres://localhost/app
+///     max_connections: 10
+///   metrics:
+///     enable: false
+/// ```
+///
+/// [`Adapter::config_key`]: crate::adapter::Adapter::config_key
+/// [`Adapter::configure`]: crate::adapter::Adapter::configure
 pub type Adapters = BTreeMap<String, serde_json::Value>;
 
+/// TLS configuration, used when the server `protocol` is `https`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ServerTls {
+    /// Path of the PEM certificate chain
+    pub cert: String,
+    /// Path of the PEM private key
+    pub key: String,
+    /// Path of the PEM CA bundle used to verify client certificates (mTLS)
+    pub client_ca: Option<String>,
+    /// Interval in milliseconds to check the files for changes, `0` disables
+    /// the reload.
+    #[serde(default = "default_tls_reload_interval")]
+    pub reload_interval: u64,
+    /// Port of a plain HTTP listener redirecting every request to HTTPS, on
+    /// the port of the first TCP listener serving the main router
+    pub redirect_port: Option<u16>,
+}
+
+fn default_tls_reload_interval() -> u64 {
+    30_000
+}
+
+/// Router served by a listener.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListenerRouter {
+    /// Routes of the application
+    #[default]
+    Main,
+    /// Routes returned by `LifeCycle::admin_routes`
+    Admin,
+}
+
+/// Address a listener binds to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ListenerBind {
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    /// Unix domain socket, an existing socket file at `path` is replaced.
+    Unix {
+        path: String,
+        /// Permissions of the socket file, for example `0o660`
+        mode: Option<u32>,
+    },
+}
+
+/// Listener of the server.
+///
+/// Example
+/// ```yaml
+/// server:
+///   listeners:
+///     - type: unix
+///       path: /run/app/app.sock
+///       mode: 0o660
+///     - type: tcp
+///       host: 127.0.0.1
+///       port: 9090
+///       router: admin
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ServerListener {
+    #[serde(flatten)]
+    pub bind: ListenerBind,
+    #[serde(default)]
+    pub router: ListenerRouter,
+}
+
 /// Application's specific settings to expose `port`,
 /// `host`, `protocol`, and possible url of the application
 /// during and after development
@@ -106,35 +311,174 @@ pub struct Server {
     pub host: String,
     pub base_url: String,
     pub protocol: String,
+    /// TLS certificates, required to serve `https`
+    pub tls: Option<ServerTls>,
+    /// Listeners of the server, replacing `host:port` when not empty
+    #[serde(default)]
+    pub listeners: Vec<ServerListener>,
+    /// Connection tuning of the listeners
+    #[serde(default)]
+    pub connection: ServerConnection,
     pub interceptions: Interceptions,
+    /// Graceful shutdown behaviour
+    #[serde(default)]
+    pub shutdown: ServerShutdown,
+    /// Adapters health checks reported by `/readyz`
+    #[serde(default)]
+    pub health: ServerHealth,
 }
 
+impl Server {
+    /// Configured listeners, or a single TCP listener on `host:port` serving
+    /// the main router when none is configured.
+    pub fn listeners(&self) -> Vec<ServerListener> {
+        if !self.listeners.is_empty() {
+            return self.listeners.clone();
+        }
+        vec![ServerListener {
+            bind: ListenerBind::Tcp {
+                host: self.host.clone(),
+                port: self.port,
+            },
+            router: ListenerRouter::Main,
+        }]
+    }
+
+    /// Whether a listener serves the admin router.
+    pub fn has_admin_listener(&self) -> bool {
+        self.listeners
+            .iter()
+            .any(|listener| listener.router == ListenerRouter::Admin)
+    }
+}
+
+/// Schedule of a job, overriding the one it was registered with.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SchedulerJob {
+    #[serde(default = "default_true")]
+    pub enable: bool,
+    /// Cron expression, `sec min hour day month weekday [year]` or the five
+    /// fields form without seconds, evaluated in UTC.
+    pub cron: Option<String>,
+    /// Interval in milliseconds between two runs
+    pub interval: Option<u64>,
+}
+
+/// Scheduled jobs configuration, keyed by job name.
+///
+/// Example
+/// ```yaml
+/// scheduler:
+///   jobs:
+///     cleanup:
+///       cron: "0 0 3 * * *"
+///     report:
+///       interval: 3600000
+///       enable: false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Scheduler {
+    #[serde(default)]
+    pub jobs: BTreeMap<String, SchedulerJob>,
+}
+
+/// Live reload of the configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConfigReload {
+    #[serde(default)]
+    pub enable: bool,
+    /// Interval in milliseconds to check the `configs` directory for
+    /// changes, `0` disables the file watcher.
+    #[serde(default = "default_reload_interval")]
+    pub interval: u64,
+    /// Reload when the process receives SIGHUP (Unix only)
+    #[serde(default = "default_true")]
+    pub signal: bool,
+}
+
+fn default_reload_interval() -> u64 {
+    2_000
+}
+
+impl Default for ConfigReload {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            interval: default_reload_interval(),
+            signal: true,
+        }
+    }
+}
+
+/// Delivery of the in-process events.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Secret {
-    // APP_SECRET__COOKIE
-    pub cookie: String,
+pub struct Events {
+    /// Number of events buffered per subscriber, publishing waits while the
+    /// buffer is full.
+    #[serde(default = "default_events_buffer")]
+    pub buffer: usize,
+    /// Maximum time in milliseconds to handle the pending events on shutdown.
+    #[serde(default = "default_events_drain_timeout")]
+    pub drain_timeout: u64,
+}
+
+fn default_events_buffer() -> usize {
+    1_024
+}
+
+fn default_events_drain_timeout() -> u64 {
+    5_000
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self {
+            buffer: default_events_buffer(),
+            drain_timeout: default_events_drain_timeout(),
+        }
+    }
+}
+
+/// Secrets of the application, redacted when the configuration is printed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Secrets {
+    // APP_SECRET__COOKIE or APP_SECRET__COOKIE__FILE
+    pub cookie: Secret<String>,
     pub token_expiration: i64,
     pub cookie_expiration: i64,
 }
 
-/// The possible runtime environment for our application.
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+/// The runtime environment of the application, its settings are loaded
+/// from `<name>.yaml` on top of `base.yaml`.
+///
+/// Any lowercase name made of letters, digits, `-` and `_` is accepted,
+/// `staging` loads `staging.yaml`. Whether an environment behaves like
+/// production is set in [`Profile`], not inferred from its name.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String")]
 pub enum Environment {
-    #[serde(rename = "development")]
     Development,
-    #[serde(rename = "production")]
     Production,
+    /// Named environment, for example `staging` or `preview`
+    Custom(String),
 }
 
 impl Environment {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Environment::Development => "development",
             Environment::Production => "production",
+            Environment::Custom(name) => name,
         }
     }
 }
 
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl TryFrom<String> for Environment {
     type Error = String;
 
@@ -142,14 +486,63 @@ impl TryFrom<String> for Environment {
         match s.to_lowercase().as_str() {
             "development" => Ok(Self::Development),
             "production" => Ok(Self::Production),
+            "base" => Err("`base` is the configuration shared by every environment".to_string()),
+            "local" => Err("`local` is the configuration overriding every environment".to_string()),
+            other
+                if !other.is_empty()
+                    && other
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Ok(Self::Custom(other.to_string()))
+            }
             other => Err(format!(
-                "{} is not a supported environment. Use either `development` or `production`.",
-                other
+                "{other:?} is not a valid environment name, use letters, digits, `-` and `_`."
             )),
         }
     }
 }
 
+/// Behaviours that differ between development and production-like
+/// environments.
+///
+/// `production` defaults to `true` for every environment but `development`,
+/// the other settings follow it when unset.
+///
+/// Example
+/// ```yaml
+/// # configs/staging.yaml
+/// profile:
+///   production: true
+///   expose_docs: true
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Profile {
+    pub production: bool,
+    /// Answer `500` when a handler panics, the connection is dropped otherwise
+    pub catch_panic: Option<bool>,
+    /// Serve the API documentation UI
+    pub expose_docs: Option<bool>,
+    /// Keep the message of the `5xx` responses, it is replaced by the status
+    /// reason otherwise
+    pub error_details: Option<bool>,
+}
+
+impl Profile {
+    pub fn catch_panic(&self) -> bool {
+        self.catch_panic.unwrap_or(!self.production)
+    }
+
+    pub fn expose_docs(&self) -> bool {
+        self.expose_docs.unwrap_or(!self.production)
+    }
+
+    pub fn error_details(&self) -> bool {
+        self.error_details.unwrap_or(!self.production)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Logger {
     /// Enable log write to stdout
@@ -164,7 +557,7 @@ pub struct Logger {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     pub server: Server,
-    pub secret: Secret,
+    pub secret: Secrets,
     pub logger: Logger,
     /// Custom app settings
     ///
@@ -175,43 +568,437 @@ pub struct Config {
     ///     secret: xxxxx
     ///     expiration: 10
     /// ```
-    /// And then optionally deserialize it to your own `Configurations` type by
-    /// accessing `ctx.settings.configurations`.
+    /// Bound at startup to the type returned by
+    /// [`crate::hook::LifeCycle::settings`] and extracted in the handlers
+    /// with [`crate::settings::Settings`].
     #[serde(default)]
     pub settings: Option<serde_json::Value>,
     pub adapters: Option<Adapters>,
+    /// Schedules of the jobs
+    #[serde(default)]
+    pub scheduler: Scheduler,
+    /// Live reload of the configuration
+    #[serde(default)]
+    pub reload: ConfigReload,
+    /// Delivery of the events of [`crate::event::EventBus`]
+    #[serde(default)]
+    pub events: Events,
+    /// Production-like behaviours of the environment
+    #[serde(default)]
+    pub profile: Profile,
+}
+
+/// Problem of the configuration, `path` is its location in the YAML files,
+/// for example `server.interceptions.cors.allow_methods[2]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every problem found by [`Config::validate`].
+#[derive(Clone, PartialEq, Eq, thiserror::Error)]
+pub struct ConfigErrors(pub Vec<ConfigIssue>);
+
+// `main` returning the error prints its `Debug`, keep one problem per line.
+impl fmt::Debug for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration, {} problem(s):", self.0.len())?;
+        for issue in &self.0 {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Methods accepted by `server.interceptions.cors.allow_methods`,
+/// [`http::Method`] parses any token as an extension method.
+const HTTP_METHODS: [&str; 9] = [
+    "GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS", "TRACE", "CONNECT",
+];
+
+#[derive(Default)]
+struct Issues(Vec<ConfigIssue>);
+
+impl Issues {
+    fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(ConfigIssue::new(path, message));
+    }
+
+    /// Record the values of `list` that `parse` rejects.
+    fn each<E: fmt::Display>(
+        &mut self,
+        path: &str,
+        list: Option<&Vec<String>>,
+        kind: &str,
+        parse: impl Fn(&str) -> Result<(), E>,
+    ) {
+        for (i, value) in list.into_iter().flatten().enumerate() {
+            if let Err(e) = parse(value) {
+                self.push(
+                    format!("{path}[{i}]"),
+                    format!("invalid {kind} {value:?}: {e}"),
+                );
+            }
+        }
+    }
+
+    fn file(&mut self, path: &str, file: &str) {
+        if !Path::new(file).exists() {
+            self.push(path, format!("file {file:?} not found"));
+        }
+    }
+}
+
+impl Config {
+    /// Check the settings the framework parses at startup, every problem is
+    /// reported with its path instead of failing on the first one.
+    ///
+    /// Enabled interceptions, the logger level, the TLS files and the jobs
+    /// schedules are checked, the adapters validate their own section in
+    /// [`crate::adapter::Adapter::configure`].
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut issues = Issues::default();
+        self.server.validate(&mut issues);
+
+        if self.logger.enable
+            && self
+                .logger
+                .level
+                .parse::<tracing::level_filters::LevelFilter>()
+                .is_err()
+        {
+            issues.push(
+                "logger.level",
+                format!(
+                    "invalid level {:?}, expected trace, debug, info, warn, error or off",
+                    self.logger.level
+                ),
+            );
+        }
+
+        for (name, job) in &self.scheduler.jobs {
+            let path = format!("scheduler.jobs.{name}");
+            match (&job.cron, job.interval) {
+                (Some(_), Some(_)) => {
+                    issues.push(path, "`cron` and `interval` are mutually exclusive")
+                }
+                (Some(cron), None) => {
+                    if let Err(e) = crate::scheduler::parse_cron(cron) {
+                        issues.push(
+                            format!("{path}.cron"),
+                            format!("invalid cron expression {cron:?}: {e}"),
+                        );
+                    }
+                }
+                (None, Some(0)) => {
+                    issues.push(format!("{path}.interval"), "must be greater than zero");
+                }
+                _ => {}
+            }
+        }
+
+        if self.events.buffer == 0 {
+            issues.push("events.buffer", "must be greater than zero");
+        }
+
+        if issues.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors(issues.0))
+        }
+    }
+}
+
+impl Server {
+    fn validate(&self, issues: &mut Issues) {
+        if !matches!(self.protocol.as_str(), "http" | "https") {
+            issues.push(
+                "server.protocol",
+                format!(
+                    "invalid protocol {:?}, expected http or https",
+                    self.protocol
+                ),
+            );
+        }
+        if let Some(tls) = self.tls.as_ref().filter(|_| self.protocol == "https") {
+            issues.file("server.tls.cert", &tls.cert);
+            issues.file("server.tls.key", &tls.key);
+            if let Some(client_ca) = &tls.client_ca {
+                issues.file("server.tls.client_ca", client_ca);
+            }
+        }
+        for (i, listener) in self.listeners.iter().enumerate() {
+            if let ListenerBind::Unix { path, .. } = &listener.bind {
+                if path.is_empty() {
+                    issues.push(format!("server.listeners[{i}].path"), "must not be empty");
+                }
+            }
+        }
+
+        let interceptions = &self.interceptions;
+        if let Some(cors) = interceptions.cors.as_ref().filter(|c| c.enable) {
+            let path = "server.interceptions.cors";
+            issues.each(
+                &format!("{path}.allow_origins"),
+                cors.allow_origins.as_ref(),
+                "origin",
+                |v| v.parse::<http::HeaderValue>().map(drop),
+            );
+            issues.each(
+                &format!("{path}.allow_headers"),
+                cors.allow_headers.as_ref(),
+                "header",
+                |v| v.parse::<http::HeaderName>().map(drop),
+            );
+            issues.each(
+                &format!("{path}.allow_methods"),
+                cors.allow_methods.as_ref(),
+                "method",
+                |v| {
+                    if HTTP_METHODS.contains(&v) {
+                        Ok(())
+                    } else {
+                        Err("invalid HTTP method")
+                    }
+                },
+            );
+        }
+        if let Some(limit) = interceptions.limit_payload.as_ref().filter(|c| c.enable) {
+            if let Err(e) = byte_unit::Byte::parse_str(&limit.body_limit, false) {
+                issues.push(
+                    "server.interceptions.limit_payload.body_limit",
+                    format!("invalid size {:?}: {e}", limit.body_limit),
+                );
+            }
+        }
+        if let Some(timeout) = interceptions.timeout_request.as_ref().filter(|c| c.enable) {
+            if timeout.timeout == 0 {
+                issues.push(
+                    "server.interceptions.timeout_request.timeout",
+                    "must be greater than zero",
+                );
+            }
+        }
+        if let Some(assets) = interceptions
+            .static_assets
+            .as_ref()
+            .filter(|c| c.enable && c.must_exist)
+        {
+            issues.file(
+                "server.interceptions.static.folder.path",
+                &assets.folder.path,
+            );
+            issues.file("server.interceptions.static.fallback", &assets.fallback);
+        }
+    }
+}
+
+/// Environment variable setting the directory of the configuration files.
+pub const CONFIG_DIR_ENV: &str = "APP_CONFIG_DIR";
+
+/// Formats of the configuration files, by extension in lookup order.
+const FORMATS: [(&str, config::FileFormat); 4] = [
+    ("yaml", config::FileFormat::Yaml),
+    ("yml", config::FileFormat::Yaml),
+    ("toml", config::FileFormat::Toml),
+    ("json", config::FileFormat::Json),
+];
+
+/// Loads the configuration of an environment, see [`LifeCycle::config_loader`].
+///
+/// Layers are merged in order, each one overriding the previous ones:
+///
+/// 1. the embedded `base` file, then `base.*` of the configuration directory
+/// 2. the embedded `<environment>` file, then `<environment>.*`
+/// 3. `local.*`, optional developer overrides kept out of git
+/// 4. the `APP_*` environment variables
+///
+/// Files are YAML (`.yaml`, `.yml`), TOML (`.toml`) or JSON (`.json`). The
+/// `base` and environment files are required unless they are embedded.
+///
+/// Example
+/// ```rust
+/// use ymir::config::ConfigLoader;
+///
+/// let loader = ConfigLoader::new()
+///     .dir(concat!(env!("CARGO_MANIFEST_DIR"), "/configs"))
+///     .embed("base.yaml", include_str!("../configs/base.yaml"));
+/// assert!(loader.config_dir().ends_with("configs"));
+/// ```
+///
+/// [`LifeCycle::config_loader`]: crate::hook::LifeCycle::config_loader
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    dir: Option<PathBuf>,
+    embedded: Vec<(String, &'static str)>,
+}
+
+impl ConfigLoader {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory of the configuration files, overriding `APP_CONFIG_DIR`.
+    #[must_use]
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Embed the file `name`, for example `base.yaml`, usually read at
+    /// compile time with `include_str!`. The files on disk override it.
+    #[must_use]
+    pub fn embed(mut self, name: impl Into<String>, contents: &'static str) -> Self {
+        self.embedded.push((name.into(), contents));
+        self
+    }
+
+    /// Directory of the configuration files: the one given to
+    /// [`ConfigLoader::dir`], `APP_CONFIG_DIR`, or `configs` in the current
+    /// directory.
+    pub fn config_dir(&self) -> PathBuf {
+        self.dir
+            .clone()
+            .or_else(|| std::env::var_os(CONFIG_DIR_ENV).map(PathBuf::from))
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default().join("configs"))
+    }
+
+    /// Load the configuration of `environment`, see [`load_configuration`].
+    pub fn load(&self, environment: &Environment) -> Result<Config, config::ConfigError> {
+        let dir = self.config_dir();
+        let mut builder = config::Config::builder().set_default(
+            "profile.production",
+            *environment != Environment::Development,
+        )?;
+        for (name, required) in [
+            ("base", true),
+            (environment.as_str(), true),
+            ("local", false),
+        ] {
+            let embedded = self.embedded(name)?;
+            let file = find_file(&dir, name)?;
+            if required && embedded.is_none() && file.is_none() {
+                return Err(config::ConfigError::Message(format!(
+                    "configuration file {:?} not found, expected a .yaml, .yml, .toml or .json file",
+                    dir.join(name)
+                )));
+            }
+            if let Some(embedded) = embedded {
+                builder = builder.add_source(embedded);
+            }
+            if let Some(file) = file {
+                builder = builder.add_source(file);
+            }
+        }
+        // The `${VAR}` references of the files are resolved before the
+        // environment variables apply, their values are taken verbatim.
+        let files = secret::Interpolated::from_source(&builder.build()?)?;
+        let (file_vars, vars): (config::Map<_, _>, config::Map<_, _>) =
+            std::env::vars().partition(|(name, _)| secret::is_file_variable(name));
+        // Add in settings from environment variables (with a prefix of APP and '__' as separator)
+        // E.g. `APP_APPLICATION__PORT=5001 would set `Settings.application.port`
+        let mut builder = config::Config::builder().add_source(files).add_source(
+            config::Environment::with_prefix("APP")
+                .prefix_separator("_")
+                .separator("__")
+                .source(Some(vars)),
+        );
+        // `APP_<PATH>__FILE` sets `<PATH>` to the content of the file.
+        for (key, value) in secret::file_settings(file_vars)? {
+            builder = builder.set_override(key, value)?;
+        }
+        builder.build()?.try_deserialize::<Config>()
+    }
+
+    /// Embedded file of the layer `name`.
+    fn embedded(
+        &self,
+        name: &str,
+    ) -> Result<
+        Option<config::File<config::FileSourceString, config::FileFormat>>,
+        config::ConfigError,
+    > {
+        let Some((file, contents)) = self
+            .embedded
+            .iter()
+            .find(|(file, _)| file.rsplit_once('.').is_some_and(|(stem, _)| stem == name))
+        else {
+            return Ok(None);
+        };
+        let format = file
+            .rsplit_once('.')
+            .and_then(|(_, ext)| FORMATS.iter().find(|(e, _)| *e == ext))
+            .map(|(_, format)| *format)
+            .ok_or_else(|| {
+                config::ConfigError::Message(format!(
+                    "unsupported format of the embedded configuration {file:?}"
+                ))
+            })?;
+        Ok(Some(config::File::from_str(contents, format)))
+    }
+}
+
+/// File of the layer `name` in `dir`, whatever its format.
+fn find_file(
+    dir: &Path,
+    name: &str,
+) -> Result<Option<config::File<config::FileSourceFile, config::FileFormat>>, config::ConfigError> {
+    let mut found = FORMATS
+        .iter()
+        .map(|(ext, format)| (dir.join(format!("{name}.{ext}")), *format))
+        .filter(|(path, _)| path.is_file());
+    let Some((path, format)) = found.next() else {
+        return Ok(None);
+    };
+    if let Some((other, _)) = found.next() {
+        return Err(config::ConfigError::Message(format!(
+            "ambiguous configuration files {path:?} and {other:?}, keep only one"
+        )));
+    }
+    Ok(Some(config::File::from(path).format(format)))
+}
+
+/// Directory holding the configuration files, see [`ConfigLoader::config_dir`].
+pub fn config_dir() -> PathBuf {
+    ConfigLoader::default().config_dir()
 }
 
 /// Multipurpose function that helps detect the current environment the application
 /// is running using the `APP_ENVIRONMENT` environment variable.
 ///
-/// APP_ENVIRONMENT = development | production.
+/// APP_ENVIRONMENT = development | production | any other name, see [`Environment`].
 ///
-/// After detection, it loads appropriate .yaml file
-/// then it loads environment variable that override whatever is set in the .yaml file.
+/// After detection, it loads appropriate configuration files, see [`ConfigLoader`],
+/// then it loads environment variable that override whatever is set in the files.
 /// For this to work, you the environment variable MUST be in uppercase and starts with `APP`,
 /// a `_` separator then the category of settings,
 /// followed by `__` separator,  and then the variable, e.g.
 /// `APP_APPLICATION__PORT=5001` for `port` to be set as `5001`
 pub fn load_configuration(environment: &Environment) -> Result<Config, config::ConfigError> {
-    let base_path = std::env::current_dir().expect("Failed to determine the current directory");
-    let config_directories = base_path.join("configs");
-
-    let environment_filename = format!("{}.yaml", environment.as_str());
-    let cfg = config::Config::builder()
-        .add_source(config::File::from(config_directories.join("base.yaml")))
-        .add_source(config::File::from(
-            config_directories.join(environment_filename),
-        ))
-        // Add in settings from environment variables (with a prefix of APP and '__' as separator)
-        // E.g. `APP_APPLICATION__PORT=5001 would set `Settings.application.port`
-        .add_source(
-            config::Environment::with_prefix("APP")
-                .prefix_separator("_")
-                .separator("__"),
-        )
-        .build()?;
-    cfg.try_deserialize::<Config>()
+    ConfigLoader::default().load(environment)
 }
 
 #[cfg(test)]
@@ -294,7 +1081,15 @@ mod tests {
             Environment::try_from("production".to_string()).unwrap(),
             Environment::Production
         );
-        assert!(Environment::try_from("invalid".to_string()).is_err());
+        assert_eq!(
+            Environment::try_from("Staging".to_string()).unwrap(),
+            Environment::Custom("staging".to_string())
+        );
+        assert_eq!(Environment::Custom("pr-42".to_string()).as_str(), "pr-42");
+        assert!(Environment::try_from("base".to_string()).is_err());
+        assert!(Environment::try_from("Local".to_string()).is_err());
+        assert!(Environment::try_from("../secrets".to_string()).is_err());
+        assert!(Environment::try_from(String::new()).is_err());
     }
 
     #[test]
@@ -305,5 +1100,203 @@ mod tests {
         assert_eq!(config.server.base_url, "http://127.0.0.1".to_string());
         assert_eq!(config.server.protocol, "http".to_string());
         assert_eq!(config.logger.level, "debug".to_string());
+        assert!(!config.profile.production);
+        assert!(config.profile.catch_panic() && config.profile.error_details());
+        assert_eq!(config.server.shutdown.pre_stop_delay, 0);
+        assert_eq!(config.server.shutdown.drain_timeout, 30_000);
+        assert_eq!(config.server.health.interval, 10_000);
+        assert_eq!(config.server.health.timeout, 2_000);
+        assert!(config.server.connection.http1.keep_alive);
+        assert_eq!(
+            config.server.connection.http1.header_read_timeout,
+            Some(30_000)
+        );
+        assert!(!config.server.connection.h2c);
+        assert_eq!(
+            config.server.listeners(),
+            vec![ServerListener {
+                bind: ListenerBind::Tcp {
+                    host: "127.0.0.1".to_string(),
+                    port: 5050
+                },
+                router: ListenerRouter::Main
+            }]
+        );
+    }
+
+    #[test]
+    fn test_config_loader() {
+        let dir = std::env::temp_dir().join(format!("ymir-configs-{}", ulid::Ulid::new()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let loader = ConfigLoader::new()
+            .dir(&dir)
+            .embed("base.yaml", include_str!("../configs/base.yaml"))
+            .embed(
+                "development.yaml",
+                include_str!("../configs/development.yaml"),
+            );
+        assert_eq!(loader.config_dir(), dir);
+
+        // The embedded files are enough.
+        let config = loader.load(&Environment::Development).unwrap();
+        assert_eq!(config.server.port, 5050);
+
+        std::fs::write(dir.join("base.toml"), "[server]\nport = 6000\n").unwrap();
+        std::fs::write(
+            dir.join("development.json"),
+            r#"{"logger": {"level": "info"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("local.yml"), "server:\n  host: 0.0.0.0\n").unwrap();
+        let config = loader.load(&Environment::Development).unwrap();
+        assert_eq!(config.server.port, 6000);
+        assert_eq!(config.logger.level, "info");
+        assert_eq!(config.server.host, "0.0.0.0");
+
+        std::fs::write(
+            dir.join("local.yml"),
+            "server:\n  host: ${YMIR_UNSET_HOST:-0.0.0.0}\n  port: ${YMIR_UNSET_PORT}\n",
+        )
+        .unwrap();
+        assert!(loader
+            .load(&Environment::Development)
+            .unwrap_err()
+            .to_string()
+            .contains("server.port: environment variable `YMIR_UNSET_PORT` is not set"));
+        std::fs::write(
+            dir.join("local.yml"),
+            "server:\n  host: ${YMIR_UNSET_HOST:-0.0.0.0}\n",
+        )
+        .unwrap();
+        let config = loader.load(&Environment::Development).unwrap();
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.secret.cookie.expose().len(), 128);
+        assert!(!format!("{config:?}").contains(config.secret.cookie.expose()));
+
+        std::fs::write(dir.join("local.yaml"), "server:\n  host: ::1\n").unwrap();
+        assert!(loader
+            .load(&Environment::Development)
+            .unwrap_err()
+            .to_string()
+            .starts_with("ambiguous configuration files"));
+
+        // Without the embedded files the environment file is required.
+        let error = ConfigLoader::new()
+            .dir(&dir)
+            .load(&Environment::Production)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("production\" not found"), "{error}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_custom_environment() {
+        let error = load_configuration(&Environment::Custom("staging".to_string()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("staging\" not found"), "{error}");
+
+        let profile = Profile {
+            production: true,
+            ..Profile::default()
+        };
+        assert!(!profile.catch_panic() && !profile.expose_docs());
+        let profile = Profile {
+            expose_docs: Some(true),
+            ..profile
+        };
+        assert!(profile.expose_docs() && !profile.error_details());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = load_configuration(&Environment::Development).unwrap();
+        assert_eq!(config.validate(), Ok(()));
+
+        let interceptions = &mut config.server.interceptions;
+        let cors = interceptions.cors.as_mut().unwrap();
+        cors.enable = true;
+        cors.allow_methods = Some(vec!["GET".into(), "POST".into(), "GETT".into()]);
+        cors.allow_headers = Some(vec!["x-ok".into(), "bad header".into()]);
+        interceptions.limit_payload = Some(InterceptionLimitPayload {
+            enable: true,
+            body_limit: "5 potatoes".to_string(),
+        });
+        config.logger.enable = true;
+        config.logger.level = "verbose".to_string();
+        config.scheduler.jobs.insert(
+            "cleanup".to_string(),
+            SchedulerJob {
+                enable: true,
+                cron: Some("every night".to_string()),
+                interval: None,
+            },
+        );
+        config.scheduler.jobs.insert(
+            "report".to_string(),
+            SchedulerJob {
+                enable: true,
+                cron: Some("0 * * * *".to_string()),
+                interval: Some(1000),
+            },
+        );
+
+        let errors = config.validate().unwrap_err();
+        let paths: Vec<_> = errors.0.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "server.interceptions.cors.allow_headers[1]",
+                "server.interceptions.cors.allow_methods[2]",
+                "server.interceptions.limit_payload.body_limit",
+                "logger.level",
+                "scheduler.jobs.cleanup.cron",
+                "scheduler.jobs.report",
+            ]
+        );
+        let message = errors.to_string();
+        assert!(message.starts_with("invalid configuration, 6 problem(s):\n"));
+        assert!(message
+            .contains("\n  server.interceptions.cors.allow_methods[2]: invalid method \"GETT\""));
+    }
+
+    #[test]
+    fn test_server_listeners() {
+        let yaml = r#"
+listeners:
+  - type: unix
+    path: /tmp/app.sock
+    mode: 0o660
+  - type: tcp
+    host: 127.0.0.1
+    port: 9090
+    router: admin
+"#;
+        let listeners: Vec<ServerListener> = config::Config::builder()
+            .add_source(config::File::from_str(yaml, config::FileFormat::Yaml))
+            .build()
+            .unwrap()
+            .get("listeners")
+            .unwrap();
+        assert_eq!(
+            listeners,
+            vec![
+                ServerListener {
+                    bind: ListenerBind::Unix {
+                        path: "/tmp/app.sock".to_string(),
+                        mode: Some(0o660)
+                    },
+                    router: ListenerRouter::Main
+                },
+                ServerListener {
+                    bind: ListenerBind::Tcp {
+                        host: "127.0.0.1".to_string(),
+                        port: 9090
+                    },
+                    router: ListenerRouter::Admin
+                },
+            ]
+        );
     }
 }
@@ -44,7 +44,7 @@ impl Context {
 
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.extend.as_ref().map_or(true, |m| m.is_empty())
+        self.extend.as_ref().is_none_or(|m| m.is_empty())
     }
 
     #[inline]
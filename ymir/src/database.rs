@@ -0,0 +1,276 @@
+//! SQL database pool.
+//!
+//! [`DatabaseAdapter`] connects a [`sqlx`] pool from the `adapters.database`
+//! configuration, runs the embedded migrations when `migrate` is set and puts
+//! the pool into the context. One cargo feature enables each driver:
+//! `postgres`, `mysql` and `sqlite`.
+//!
+//! Example
+//! ```rust,ignore
+//! static MIGRATOR: Migrator = sqlx::migrate!();
+//!
+//! async fn adapters() -> Result<Vec<Box<dyn Adapter>>> {
+//!     Ok(vec![Box::new(PostgresAdapter::default().migrator(&MIGRATOR))])
+//! }
+//!
+//! let pool = ctx.get::<PgPool>().expect("database adapter is registered");
+//! ```
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use sqlx::{migrate::Migrate, pool::PoolOptions, Connection, Database, Pool};
+
+pub use sqlx::migrate::Migrator;
+
+use crate::{
+    adapter::{Adapter, AdapterConfig, AdapterPolicy, HookTimeouts},
+    context::Context,
+    errors::Error,
+    secret::Secret,
+    Result,
+};
+
+/// Configuration of the [`DatabaseAdapter`], bound from `adapters.database`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Connection URL, `postgres://`, `mysql://` or `sqlite:`, redacted when
+    /// printed as it may hold the password
+    pub url: Secret<String>,
+    /// Maximum number of connections of the pool
+    pub max_connections: u32,
+    /// Number of connections kept open
+    pub min_connections: u32,
+    /// Maximum time in milliseconds to acquire a connection
+    pub acquire_timeout: u64,
+    /// Time in milliseconds after which an idle connection is closed, none
+    /// keeps it open
+    pub idle_timeout: Option<u64>,
+    /// Run the embedded migrations on startup
+    pub migrate: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: Secret::default(),
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: 5000,
+            idle_timeout: Some(600_000),
+            migrate: false,
+        }
+    }
+}
+
+/// Adapter owning the [`Pool`] of a database, set in the context as
+/// `Pool<DB>` (`PgPool`, `MySqlPool`, `SqlitePool`).
+///
+/// The adapter is required and reports the database unreachable to
+/// `/readyz`, the pool is closed in `before_stop`.
+pub struct DatabaseAdapter<DB: Database> {
+    config: DatabaseConfig,
+    migrator: Option<&'static Migrator>,
+    pool: Option<Pool<DB>>,
+}
+
+#[cfg(feature = "postgres")]
+pub type PostgresAdapter = DatabaseAdapter<sqlx::Postgres>;
+#[cfg(feature = "mysql")]
+pub type MySqlAdapter = DatabaseAdapter<sqlx::MySql>;
+#[cfg(feature = "sqlite")]
+pub type SqliteAdapter = DatabaseAdapter<sqlx::Sqlite>;
+
+impl<DB: Database> DatabaseAdapter<DB> {
+    /// Migrations run on startup when `adapters.database.migrate` is set,
+    /// usually embedded with `sqlx::migrate!()`.
+    #[must_use]
+    pub fn migrator(mut self, migrator: &'static Migrator) -> Self {
+        self.migrator = Some(migrator);
+        self
+    }
+
+    /// Pool of the adapter, available once initialized.
+    pub fn pool(&self) -> Option<&Pool<DB>> {
+        self.pool.as_ref()
+    }
+
+    fn connected(&self) -> Result<&Pool<DB>> {
+        self.pool
+            .as_ref()
+            .ok_or_else(|| Error::string("database pool is not connected"))
+    }
+}
+
+impl<DB: Database> Default for DatabaseAdapter<DB> {
+    fn default() -> Self {
+        Self {
+            config: DatabaseConfig::default(),
+            migrator: None,
+            pool: None,
+        }
+    }
+}
+
+impl<DB: Database> fmt::Debug for DatabaseAdapter<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatabaseAdapter")
+            .field("driver", &DB::NAME)
+            .field("migrate", &self.config.migrate)
+            .field("connected", &self.pool.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<DB> Adapter for DatabaseAdapter<DB>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    fn name(&self) -> String {
+        "database".to_string()
+    }
+
+    fn policy(&self) -> AdapterPolicy {
+        AdapterPolicy {
+            timeouts: HookTimeouts {
+                before_stop: Some(Duration::from_secs(10)),
+                ..HookTimeouts::default()
+            },
+            ..AdapterPolicy::default()
+        }
+    }
+
+    fn configure(&mut self, config: AdapterConfig<'_>) -> Result<()> {
+        self.config = config.bind()?;
+        if self.config.url.expose().is_empty() {
+            return Err(Error::Message(format!(
+                "missing configuration `adapters.{}.url`",
+                config.key()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn init(&mut self) -> Result<()> {
+        let pool = PoolOptions::<DB>::new()
+            .max_connections(self.config.max_connections)
+            .min_connections(self.config.min_connections)
+            .acquire_timeout(Duration::from_millis(self.config.acquire_timeout))
+            .idle_timeout(self.config.idle_timeout.map(Duration::from_millis))
+            .connect(self.config.url.expose())
+            .await
+            .map_err(|e| Error::Message(format!("failed to connect to the database: {e}")))?;
+
+        if self.config.migrate {
+            let migrator = self.migrator.ok_or_else(|| {
+                Error::string("`migrate` is set but the database adapter has no migrator")
+            })?;
+            migrator
+                .run(&pool)
+                .await
+                .map_err(|e| Error::Message(format!("failed to run the migrations: {e}")))?;
+            tracing::info!(
+                migrations = migrator.iter().count(),
+                "database migrations applied"
+            );
+        }
+        self.pool = Some(pool);
+        Ok(())
+    }
+
+    async fn before_run(&mut self, mut ctx: Context) -> Result<Context> {
+        ctx.set(self.connected()?.clone());
+        Ok(ctx)
+    }
+
+    async fn health(&self, _ctx: &Context) -> Result<()> {
+        let mut conn = self.connected()?.acquire().await.map_err(Error::wrap)?;
+        conn.ping().await.map_err(Error::wrap)
+    }
+
+    async fn before_stop(&self, _ctx: &Context) -> Result<()> {
+        if let Some(pool) = &self.pool {
+            pool.close().await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::adapter::AdapterManager;
+    use sqlx::SqlitePool;
+
+    fn database_context(database: serde_json::Value) -> Context {
+        crate::testing::context_with(|cfg| {
+            cfg.adapters =
+                serde_json::from_value(serde_json::json!({ "database": database })).unwrap();
+        })
+    }
+
+    async fn migrator() -> &'static Migrator {
+        let dir = std::env::temp_dir().join(format!("ymir-migrations-{}", ulid::Ulid::new()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("1_users.sql"),
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);",
+        )
+        .unwrap();
+        let migrator = Migrator::new(dir.as_path()).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        Box::leak(Box::new(migrator))
+    }
+
+    #[tokio::test]
+    async fn test_database_adapter() {
+        // Every connection to `sqlite::memory:` opens its own database.
+        let ctx = database_context(serde_json::json!({
+            "url": "sqlite::memory:",
+            "max_connections": 1,
+            "idle_timeout": null,
+            "migrate": true,
+        }));
+        let mut manager = AdapterManager::new(ctx);
+        manager.register(Box::new(
+            SqliteAdapter::default().migrator(migrator().await),
+        ));
+        manager.init_all().await.unwrap();
+        let ctx = manager.before_run().await.unwrap();
+
+        let pool = ctx.get::<SqlitePool>().unwrap().clone();
+        sqlx::query("INSERT INTO users (name) VALUES ('ymir')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(manager.check_health().await);
+
+        manager.stop_all().await.unwrap();
+        assert!(pool.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_database_configuration_errors() {
+        let mut manager = AdapterManager::new(database_context(serde_json::json!({})));
+        manager.register(Box::new(SqliteAdapter::default()));
+        assert_eq!(
+            manager.init_all().await.unwrap_err().to_string(),
+            "missing configuration `adapters.database.url`"
+        );
+
+        let mut manager = AdapterManager::new(database_context(serde_json::json!({
+            "url": "sqlite::memory:",
+            "migrate": true,
+        })));
+        manager.register(Box::new(SqliteAdapter::default()));
+        assert!(manager
+            .init_all()
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("`migrate` is set but the database adapter has no migrator"));
+    }
+}
@@ -67,6 +67,9 @@ pub enum Error {
     #[error(transparent)]
     InvalidMethod(#[from] InvalidMethod),
 
+    #[error(transparent)]
+    InvalidConfig(#[from] crate::config::ConfigErrors),
+
     #[error(transparent)]
     Any(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
@@ -0,0 +1,338 @@
+//! In-process bus of typed events.
+//!
+//! Subscribers are registered from [`crate::hook::LifeCycle::subscribers`]
+//! and [`crate::adapter::Adapter::subscribers`] once the adapters ran
+//! `before_run`. Each subscriber receives the events of its type in order
+//! through a buffer of `events.buffer` events, publishing waits while a
+//! buffer is full.
+//!
+//! On shutdown the bus stops accepting events and the pending ones are
+//! handled within `events.drain_timeout` before the adapters stop.
+//!
+//! Example
+//! ```rust
+//! use ymir::{context::Context, event::Subscriber};
+//!
+//! #[derive(Debug, Clone)]
+//! struct UserCreated {
+//!     id: u64,
+//! }
+//!
+//! let subscriber = Subscriber::new("welcome-email", |_ctx: Context, event: UserCreated| async move {
+//!     tracing::info!(user = event.id, "sending welcome email");
+//!     Ok(())
+//! });
+//! assert_eq!(subscriber.name(), "welcome-email");
+//!
+//! // In a handler:
+//! // event::publish(&ctx, UserCreated { id: 1 }).await?;
+//! ```
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::Duration,
+};
+
+use tokio::{sync::mpsc, task::JoinSet};
+
+use crate::{config::Events, context::Context, errors::Error, worker::WorkerFuture, Result};
+
+type Payload = Box<dyn Any + Send>;
+type HandlerFn = Arc<dyn Fn(Context, Payload) -> WorkerFuture + Send + Sync>;
+
+/// A named handler of the events of one type.
+#[derive(Clone)]
+pub struct Subscriber {
+    name: String,
+    event: TypeId,
+    event_name: &'static str,
+    handle: HandlerFn,
+}
+
+impl Debug for Subscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("name", &self.name)
+            .field("event", &self.event_name)
+            .finish()
+    }
+}
+
+impl Subscriber {
+    pub fn new<E, F, Fut>(name: impl Into<String>, handle: F) -> Self
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(Context, E) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            event: TypeId::of::<E>(),
+            event_name: type_name::<E>(),
+            handle: Arc::new(move |ctx, payload| {
+                // Payloads are routed by `TypeId`, the downcast cannot fail.
+                let event = *payload.downcast::<E>().expect("event routed by type");
+                Box::pin(handle(ctx, event))
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Clone)]
+struct Route {
+    name: String,
+    tx: mpsc::Sender<Payload>,
+}
+
+struct Inner {
+    config: Events,
+    /// `None` once the bus is drained.
+    routes: RwLock<Option<HashMap<TypeId, Vec<Route>>>>,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+/// Bus set in the context by `startup::boot`.
+#[derive(Clone)]
+pub struct EventBus(Arc<Inner>);
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(Events::default())
+    }
+}
+
+impl Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("config", &self.0.config)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl EventBus {
+    #[must_use]
+    pub fn new(config: Events) -> Self {
+        Self(Arc::new(Inner {
+            config,
+            routes: RwLock::new(Some(HashMap::new())),
+            tasks: Mutex::default(),
+        }))
+    }
+
+    /// Start delivering the events of `subscriber`, its handler receives
+    /// `ctx`.
+    pub fn subscribe(&self, ctx: &Context, subscriber: Subscriber) -> Result<()> {
+        let mut routes = self
+            .0
+            .routes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let routes = routes
+            .as_mut()
+            .ok_or_else(|| Error::string("event bus is closed"))?;
+        let (tx, mut rx) = mpsc::channel::<Payload>(self.0.config.buffer.max(1));
+        routes.entry(subscriber.event).or_default().push(Route {
+            name: subscriber.name.clone(),
+            tx,
+        });
+
+        let ctx = ctx.clone();
+        self.0
+            .tasks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .spawn(async move {
+                while let Some(payload) = rx.recv().await {
+                    if let Err(e) = (subscriber.handle)(ctx.clone(), payload).await {
+                        tracing::error!(
+                            subscriber = subscriber.name,
+                            event = subscriber.event_name,
+                            error = %e,
+                            "event handler failed"
+                        );
+                    }
+                }
+            });
+        Ok(())
+    }
+
+    /// Deliver `event` to every subscriber of its type, waiting while the
+    /// buffer of a subscriber is full.
+    pub async fn publish<E: Clone + Send + Sync + 'static>(&self, event: E) -> Result<()> {
+        let routes = self
+            .0
+            .routes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .ok_or_else(|| Error::string("event bus is closed"))?
+            .get(&TypeId::of::<E>())
+            .cloned()
+            .unwrap_or_default();
+        for route in routes {
+            if route.tx.send(Box::new(event.clone())).await.is_err() {
+                tracing::warn!(
+                    subscriber = route.name,
+                    event = type_name::<E>(),
+                    "event dropped, the subscriber stopped"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0
+            .routes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_none()
+    }
+
+    /// Stop accepting events and wait up to `events.drain_timeout` for the
+    /// pending ones to be handled, returns whether they all were.
+    pub async fn drain(&self) -> bool {
+        self.0
+            .routes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let mut tasks =
+            std::mem::take(&mut *self.0.tasks.lock().unwrap_or_else(PoisonError::into_inner));
+        let timeout = Duration::from_millis(self.0.config.drain_timeout);
+        let drained = tokio::time::timeout(timeout, async {
+            while let Some(joined) = tasks.join_next().await {
+                if let Err(e) = joined {
+                    tracing::error!(error = %e, "event subscriber panicked");
+                }
+            }
+        })
+        .await
+        .is_ok();
+        if !drained {
+            tracing::warn!(
+                timeout = ?timeout,
+                "event drain timeout reached, pending events are dropped"
+            );
+        }
+        drained
+    }
+}
+
+/// Publish `event` on the [`EventBus`] of the context.
+pub async fn publish<E: Clone + Send + Sync + 'static>(ctx: &Context, event: E) -> Result<()> {
+    ctx.get::<EventBus>()
+        .ok_or_else(|| Error::string("the context has no event bus"))?
+        .publish(event)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct UserCreated(u32);
+
+    #[derive(Debug, Clone)]
+    struct OrderPlaced;
+
+    fn bus(buffer: usize) -> (Context, EventBus) {
+        let bus = EventBus::new(Events {
+            buffer,
+            drain_timeout: 1000,
+        });
+        let mut ctx = Context::default();
+        ctx.set(bus.clone());
+        (ctx, bus)
+    }
+
+    #[tokio::test]
+    async fn test_publish_to_subscribers_of_the_type() {
+        let (ctx, bus) = bus(8);
+        let received = Arc::new(Mutex::new(vec![]));
+        for name in ["audit", "email"] {
+            let received = received.clone();
+            bus.subscribe(
+                &ctx,
+                Subscriber::new(name, move |_ctx, event: UserCreated| {
+                    let received = received.clone();
+                    async move {
+                        received.lock().unwrap().push((name, event.0));
+                        Ok(())
+                    }
+                }),
+            )
+            .unwrap();
+        }
+        let orders = Arc::new(AtomicU32::new(0));
+        let counter = orders.clone();
+        bus.subscribe(
+            &ctx,
+            Subscriber::new("orders", move |_ctx, _event: OrderPlaced| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Err(Error::string("handler errors are logged"))
+                }
+            }),
+        )
+        .unwrap();
+
+        publish(&ctx, UserCreated(1)).await.unwrap();
+        publish(&ctx, UserCreated(2)).await.unwrap();
+        bus.publish(OrderPlaced).await.unwrap();
+        // Events without subscriber are dropped.
+        bus.publish(42_u8).await.unwrap();
+        assert!(bus.drain().await);
+
+        let mut received = received.lock().unwrap().clone();
+        received.sort_unstable();
+        assert_eq!(
+            received,
+            [("audit", 1), ("audit", 2), ("email", 1), ("email", 2)]
+        );
+        assert_eq!(orders.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            bus.publish(UserCreated(3)).await.unwrap_err().to_string(),
+            "event bus is closed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drain_handles_pending_events() {
+        let (ctx, bus) = bus(1);
+        let handled = Arc::new(AtomicU32::new(0));
+        let counter = handled.clone();
+        bus.subscribe(
+            &ctx,
+            Subscriber::new("slow", move |_ctx, _event: UserCreated| {
+                let counter = counter.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            }),
+        )
+        .unwrap();
+
+        // The buffer holds one event, publishing waits for the handler.
+        for id in 0..3 {
+            bus.publish(UserCreated(id)).await.unwrap();
+        }
+        assert!(handled.load(Ordering::SeqCst) < 3);
+        assert!(bus.drain().await);
+        assert_eq!(handled.load(Ordering::SeqCst), 3);
+        assert!(bus.is_closed());
+    }
+}
@@ -1,14 +1,97 @@
-use axum::response::Response;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use axum::{
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use http::StatusCode;
 use serde::Serialize;
-use utoipa::ToSchema;
+use utoipa::{OpenApi, ToSchema};
 
-use crate::{render, Result};
+use crate::{
+    adapter::{AdapterState, AdapterStatus, AdapterStatuses},
+    render,
+    responses::Json,
+    Result,
+};
 
 #[derive(Serialize, ToSchema)]
 pub struct Health {
     pub ok: bool,
 }
 
+/// Body of `/readyz`.
+#[derive(Serialize, ToSchema)]
+pub struct Ready {
+    pub ok: bool,
+    /// State of each adapter
+    pub adapters: Vec<AdapterReadiness>,
+}
+
+/// State of an adapter reported by `/readyz`.
+#[derive(Serialize, ToSchema)]
+pub struct AdapterReadiness {
+    pub name: String,
+    pub state: AdapterState,
+    pub required: bool,
+    pub healthy: bool,
+    /// Error of the failed hook or health check, only reported with
+    /// [`ErrorDetails`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AdapterReadiness {
+    fn new(status: AdapterStatus, details: bool) -> Self {
+        let healthy = status.is_healthy();
+        let error = status
+            .error
+            .or_else(|| status.health.and_then(|h| h.error))
+            .filter(|_| details);
+        Self {
+            name: status.name,
+            state: status.state,
+            required: status.required,
+            healthy,
+            error,
+        }
+    }
+}
+
+/// Whether the health endpoints report the errors of the adapters, which
+/// may hold hostnames or driver messages.
+///
+/// The main router follows `profile.error_details`, the admin router always
+/// reports them. Without it the errors are hidden.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorDetails(pub bool);
+
+/// Readiness of the application reported by `/readyz`.
+///
+/// It is flipped to not ready when the graceful shutdown starts.
+#[derive(Debug, Clone)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set_ready(&self, ready: bool) {
+        self.0.store(ready, Ordering::SeqCst);
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/healthz",
@@ -27,10 +110,74 @@ pub async fn healthz() -> Result<Response> {
     path = "/readyz",
     tag = "status",
     responses(
-        (status = OK, description = "Success", body = Health),
+        (status = OK, description = "Success", body = Ready),
+        (status = 400, description = "Bad Request", body = crate::errors::ErrorResponse),
+        (status = 503, description = "Shutting down or a required adapter is unhealthy", body = Ready)
+    )
+)]
+pub async fn readyz(
+    readiness: Option<Extension<Readiness>>,
+    statuses: Option<Extension<AdapterStatuses>>,
+    details: Option<Extension<ErrorDetails>>,
+) -> Result<Response> {
+    let details = details.is_some_and(|Extension(ErrorDetails(details))| details);
+    let adapters: Vec<_> = statuses
+        .as_ref()
+        .map(|Extension(s)| s.snapshot())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|status| AdapterReadiness::new(status, details))
+        .collect();
+    let ok = readiness.is_none_or(|Extension(r)| r.is_ready())
+        && adapters.iter().all(|s| !s.required || s.healthy);
+    if !ok {
+        return Ok((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(Ready { ok, adapters }),
+        )
+            .into_response());
+    }
+    render::json(Ready { ok, adapters })
+}
+
+#[utoipa::path(
+    get,
+    path = "/adapters",
+    tag = "status",
+    responses(
+        (status = OK, description = "State of each adapter in execution order", body = [AdapterStatus]),
         (status = 400, description = "Bad Request", body = crate::errors::ErrorResponse)
     )
 )]
-pub async fn readyz() -> Result<Response> {
-    render::json(Health { ok: true })
+pub async fn adapters(
+    statuses: Option<Extension<AdapterStatuses>>,
+    details: Option<Extension<ErrorDetails>>,
+) -> Result<Response> {
+    let details = details.is_some_and(|Extension(ErrorDetails(details))| details);
+    let mut statuses = statuses
+        .map(|Extension(s)| s.snapshot())
+        .unwrap_or_default();
+    if !details {
+        for status in &mut statuses {
+            status.error = None;
+            if let Some(health) = status.health.as_mut() {
+                health.error = None;
+            }
+        }
+    }
+    render::json(statuses)
+}
+
+/// OpenAPI document of the health endpoints served by [`routes`].
+#[derive(OpenApi)]
+#[openapi(paths(healthz, readyz, adapters))]
+pub struct HealthApi;
+
+/// Health endpoints ymir serves on the admin router, `/healthz`, `/readyz`
+/// and `/adapters`, ahead of [`crate::hook::LifeCycle::admin_routes`].
+pub fn routes() -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/adapters", get(adapters))
 }
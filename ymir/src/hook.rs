@@ -1,9 +1,10 @@
-use std::net::SocketAddr;
-
 use async_trait::async_trait;
 use axum::Router;
 
-use crate::{adapter::Adapter, context::Context, errors::Error, signal::shutdown_signal, Result};
+use crate::{
+    adapter::Adapter, config::ConfigLoader, context::Context, event::Subscriber, scheduler::Job,
+    server, settings::SettingsBinding, signal, worker::Worker, Result,
+};
 
 #[async_trait]
 pub trait LifeCycle {
@@ -21,29 +22,25 @@ pub trait LifeCycle {
     /// ```
     fn app_name() -> &'static str;
 
-    /// Start serving the Axum web application on the specified address and
-    /// port.
+    /// Start serving the Axum web application on the configured listeners,
+    /// over TLS when `server.protocol` is `https`. `admin` holds the
+    /// [`LifeCycle::admin_routes`] served on the `admin` listeners.
+    ///
+    /// The server must stop accepting connections once
+    /// [`signal::drain_signal`] resolves, `startup::run` then waits for it to
+    /// drain up to `server.shutdown.drain_timeout`.
     ///
     /// # Returns
     /// A Result indicating success () or an error if the server fails to start.
-    async fn rest(ctx: &Context, app: Router) -> Result<()> {
-        let settings = ctx.configs.clone().expect("load configuration failed.");
-        let address = format!("{}:{}", settings.server.host, settings.server.port);
-
-        let listener = tokio::net::TcpListener::bind(&address).await?;
-
-        tracing::info!("Listening on {}", &address);
+    async fn rest(ctx: &Context, app: Router, admin: Option<Router>) -> Result<()> {
+        server::serve(ctx, app, admin).await
+    }
 
-        match axum::serve(
-            listener,
-            app.into_make_service_with_connect_info::<SocketAddr>(),
-        )
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        {
-            Ok(()) => Ok(()),
-            Err(e) => Err(Error::Message(format!("{}", e))),
-        }
+    /// Future resolving when the application must shut down.
+    ///
+    /// Defaults to Ctrl+C or SIGTERM.
+    async fn shutdown_signal() {
+        signal::shutdown_signal().await;
     }
 
     /// Register external adapters to the application.
@@ -51,6 +48,73 @@ pub trait LifeCycle {
         Ok(vec![])
     }
 
+    /// Register background workers supervised next to the HTTP server.
+    ///
+    /// Workers are started once the adapters ran `before_run`, and are
+    /// notified through the adapters shutdown signal when the server stops.
+    async fn workers(_ctx: &Context) -> Result<Vec<Worker>> {
+        Ok(vec![])
+    }
+
+    /// Register jobs run periodically next to the HTTP server.
+    ///
+    /// Their schedule can be overridden per environment in
+    /// `scheduler.jobs.<name>`, see [`crate::scheduler`].
+    async fn jobs(_ctx: &Context) -> Result<Vec<Job>> {
+        Ok(vec![])
+    }
+
+    /// Register the subscribers of the events published on the
+    /// [`crate::event::EventBus`] of the context, see [`crate::event`].
+    async fn subscribers(_ctx: &Context) -> Result<Vec<Subscriber>> {
+        Ok(vec![])
+    }
+
+    /// Type of the `settings` section of the configuration, bound and
+    /// validated before the adapters run, see [`crate::settings`].
+    fn settings() -> Option<SettingsBinding> {
+        None
+    }
+
+    /// Loader of the configuration files, `configs` of the current directory
+    /// or `APP_CONFIG_DIR` by default.
+    ///
+    /// Example
+    /// ```rust,ignore
+    /// fn config_loader() -> ConfigLoader {
+    ///     ConfigLoader::new()
+    ///         .dir("/etc/app")
+    ///         .embed("base.yaml", include_str!("../configs/base.yaml"))
+    /// }
+    /// ```
+    fn config_loader() -> ConfigLoader {
+        ConfigLoader::default()
+    }
+
+    /// OpenAPI document of the application, exported by the `openapi export`
+    /// command of [`crate::cli`].
+    fn openapi() -> Option<utoipa::openapi::OpenApi> {
+        None
+    }
+
     /// Router
     fn routes(ctx: Context) -> Router;
+
+    /// Internal routes (metrics, docs) served only on the listeners of
+    /// `server.listeners` using the `admin` router, next to the health
+    /// endpoints `/healthz`, `/readyz` and `/adapters` ymir serves there.
+    ///
+    /// Example
+    /// ```rust,ignore
+    /// fn admin_routes(ctx: Context) -> Option<Router> {
+    ///     Some(
+    ///         Router::new()
+    ///             .route("/metrics", get(metrics))
+    ///             .with_state(ctx),
+    ///     )
+    /// }
+    /// ```
+    fn admin_routes(_ctx: Context) -> Option<Router> {
+        None
+    }
 }
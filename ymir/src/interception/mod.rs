@@ -1,3 +1,4 @@
+mod reloadable;
 pub mod request_id;
 
 use std::{sync::LazyLock, time::Duration};
@@ -9,7 +10,14 @@ use tower_http::{
     set_header::SetResponseHeaderLayer, timeout::TimeoutLayer,
 };
 
-use crate::{config::Environment, context::Context, errors::Error, Result};
+use crate::{
+    context::Context,
+    errors::{Error, ErrorResponse},
+    reload::ConfigWatch,
+    responses::Json,
+    timeline::StartupTimeline,
+    Result,
+};
 
 static DEFAULT_IDENT_HEADER_NAME: LazyLock<http::header::HeaderName> =
     LazyLock::new(|| http::header::HeaderName::from_static("x-powered-by"));
@@ -17,24 +25,58 @@ static DEFAULT_IDENT_HEADER_NAME: LazyLock<http::header::HeaderName> =
 static DEFAULT_IDENT_HEADER_VALUE: LazyLock<http::header::HeaderValue> =
     LazyLock::new(|| http::header::HeaderValue::from_static("butter"));
 
-pub fn interception_fn(ctx: Context, mut router: Router) -> Router {
-    let cfg = ctx.configs.clone().expect("load configuration failed.");
-
-    // CORS Middleware
-    if let Some(cors) = cfg.server.interceptions.cors.as_ref().filter(|c| c.enable) {
-        router = router.layer(tower::ServiceBuilder::new().layer(interception_cors(cors).unwrap()));
-        tracing::info!("[Middleware] +cors");
-    }
-    // Timeout Middleware
-    if let Some(timeout) = cfg
-        .server
-        .interceptions
-        .timeout_request
-        .as_ref()
-        .filter(|c| c.enable)
-    {
-        router = router.layer(TimeoutLayer::new(Duration::from_millis(timeout.timeout)));
-        tracing::info!("[Middleware] +timeout");
+/// Add the interception layers enabled in `server.interceptions`.
+///
+/// The configuration is expected to pass [`crate::config::Config::validate`],
+/// an invalid value is returned as an error.
+pub fn interception_fn(ctx: Context, mut router: Router) -> Result<Router> {
+    let cfg = ctx
+        .configs
+        .clone()
+        .ok_or_else(|| Error::string("the context has no configuration"))?;
+    let timeline = ctx.get::<StartupTimeline>().cloned().unwrap_or_default();
+
+    // CORS and Timeout Middleware, follow the published configuration when
+    // the live reload is enabled.
+    match ctx.get::<ConfigWatch>().filter(|_| cfg.reload.enable) {
+        Some(watch) => {
+            router = timeline.step("interception cors", || {
+                router.layer(axum::middleware::from_fn_with_state(
+                    reloadable::cors(watch),
+                    reloadable::cors_middleware,
+                ))
+            });
+            router = timeline.step("interception timeout", || {
+                router.layer(axum::middleware::from_fn_with_state(
+                    reloadable::timeout(watch),
+                    reloadable::timeout_middleware,
+                ))
+            });
+            tracing::info!("[Middleware] +cors +timeout (reloadable)");
+        }
+        None => {
+            // CORS Middleware
+            if let Some(cors) = cfg.server.interceptions.cors.as_ref().filter(|c| c.enable) {
+                let cors = interception_cors(cors)?;
+                router = timeline.step("interception cors", || {
+                    router.layer(tower::ServiceBuilder::new().layer(cors))
+                });
+                tracing::info!("[Middleware] +cors");
+            }
+            // Timeout Middleware
+            if let Some(timeout) = cfg
+                .server
+                .interceptions
+                .timeout_request
+                .as_ref()
+                .filter(|c| c.enable)
+            {
+                router = timeline.step("interception timeout", || {
+                    router.layer(TimeoutLayer::new(Duration::from_millis(timeout.timeout)))
+                });
+                tracing::info!("[Middleware] +timeout");
+            }
+        }
     }
 
     // Compression Middleware
@@ -46,7 +88,9 @@ pub fn interception_fn(ctx: Context, mut router: Router) -> Router {
         .filter(|c| c.enable)
         .is_some()
     {
-        router = router.layer(CompressionLayer::new());
+        router = timeline.step("interception compression", || {
+            router.layer(CompressionLayer::new())
+        });
         tracing::info!("[Middleware] +compression");
     }
 
@@ -58,31 +102,44 @@ pub fn interception_fn(ctx: Context, mut router: Router) -> Router {
         .as_ref()
         .filter(|c| c.enable)
     {
-        router = router.layer(axum::extract::DefaultBodyLimit::max(
-            byte_unit::Byte::parse_str(&limit.body_limit, false)
-                .unwrap()
-                .as_u128() as usize,
-        ));
+        let body_limit = byte_unit::Byte::parse_str(&limit.body_limit, false).map_err(|e| {
+            Error::Message(format!("invalid body limit `{}`: {e}", limit.body_limit))
+        })?;
+        router = timeline.step("interception limit payload", || {
+            router.layer(axum::extract::DefaultBodyLimit::max(
+                usize::try_from(body_limit.as_u128()).unwrap_or(usize::MAX),
+            ))
+        });
         tracing::info!(data = &limit.body_limit, "[Middleware] +limit payload");
     }
 
     // catch panic
-    match ctx.environment.unwrap() {
-        Environment::Development => {
-            router = router.layer(CatchPanicLayer::custom(handle_panic));
-        }
-        // TODO! Production Env.
-        Environment::Production => (),
+    if cfg.profile.catch_panic() {
+        router = timeline.step("interception catch panic", || {
+            router.layer(CatchPanicLayer::custom(handle_panic))
+        });
     }
 
-    router = router.layer(SetResponseHeaderLayer::overriding(
-        DEFAULT_IDENT_HEADER_NAME.clone(),
-        DEFAULT_IDENT_HEADER_VALUE.clone(),
-    ));
+    // Error details, hidden in production-like environments
+    if !cfg.profile.error_details() {
+        router = timeline.step("interception error details", || {
+            router.layer(axum::middleware::map_response(hide_error_details))
+        });
+        tracing::info!("[Middleware] +hide error details");
+    }
 
-    router = router.layer(axum::middleware::from_fn(request_id_middleware));
+    router = timeline.step("interception ident header", || {
+        router.layer(SetResponseHeaderLayer::overriding(
+            DEFAULT_IDENT_HEADER_NAME.clone(),
+            DEFAULT_IDENT_HEADER_VALUE.clone(),
+        ))
+    });
 
-    router
+    router = timeline.step("interception request id", || {
+        router.layer(axum::middleware::from_fn(request_id_middleware))
+    });
+
+    Ok(router)
 }
 
 pub fn interception_cors(cfg: &crate::config::InterceptionCors) -> Result<cors::CorsLayer> {
@@ -91,7 +148,11 @@ pub fn interception_cors(cfg: &crate::config::InterceptionCors) -> Result<cors::
     if let Some(allow_origins) = &cfg.allow_origins {
         let mut list = vec![];
         for origins in allow_origins {
-            list.push(origins.parse::<axum::http::HeaderValue>().unwrap());
+            list.push(
+                origins
+                    .parse::<axum::http::HeaderValue>()
+                    .map_err(|e| cors_error("origin", origins, e))?,
+            );
         }
         cors = cors.allow_origin(list);
     }
@@ -99,7 +160,11 @@ pub fn interception_cors(cfg: &crate::config::InterceptionCors) -> Result<cors::
     if let Some(allow_headers) = &cfg.allow_headers {
         let mut headers = vec![];
         for header in allow_headers {
-            headers.push(header.parse().unwrap());
+            headers.push(
+                header
+                    .parse::<http::HeaderName>()
+                    .map_err(|e| cors_error("header", header, e))?,
+            );
         }
         cors = cors.allow_headers(headers);
     }
@@ -107,7 +172,11 @@ pub fn interception_cors(cfg: &crate::config::InterceptionCors) -> Result<cors::
     if let Some(allow_methods) = &cfg.allow_methods {
         let mut methods = vec![];
         for method in allow_methods {
-            methods.push(method.parse().unwrap());
+            methods.push(
+                method
+                    .parse::<http::Method>()
+                    .map_err(|e| cors_error("method", method, e))?,
+            );
         }
         cors = cors.allow_methods(methods);
     }
@@ -118,6 +187,23 @@ pub fn interception_cors(cfg: &crate::config::InterceptionCors) -> Result<cors::
     Ok(cors)
 }
 
+fn cors_error(kind: &str, value: &str, e: impl std::fmt::Display) -> Error {
+    Error::Message(format!("invalid cors {kind} `{value}`: {e}"))
+}
+
+/// Replace the body of the `5xx` responses with their status reason.
+async fn hide_error_details(response: axum::response::Response) -> axum::response::Response {
+    let status = response.status();
+    if !status.is_server_error() {
+        return response;
+    }
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(http::header::CONTENT_LENGTH);
+    parts.headers.remove(http::header::CONTENT_TYPE);
+    let message = status.canonical_reason().unwrap_or("Internal Server Error");
+    (parts, Json(ErrorResponse::new(status, message))).into_response()
+}
+
 /// Handler function for the [`CatchPanicLayer`] middleware.
 #[allow(clippy::needless_pass_by_value)]
 fn handle_panic(err: Box<dyn std::any::Any + Send + 'static>) -> axum::response::Response {
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::StatusCode;
+use tower::{Layer, ServiceExt};
+use tower_http::cors::CorsLayer;
+
+use crate::reload::{ConfigWatch, Reloadable};
+
+use super::interception_cors;
+
+/// CORS layer following the `server.interceptions.cors` configuration, an
+/// invalid configuration is rejected by the reload before it is published.
+pub fn cors(watch: &ConfigWatch) -> Reloadable<Option<CorsLayer>> {
+    Reloadable::new(watch, |cfg| {
+        cfg.server
+            .interceptions
+            .cors
+            .as_ref()
+            .filter(|c| c.enable)
+            .and_then(|c| interception_cors(c).ok())
+    })
+}
+
+pub async fn cors_middleware(
+    State(cors): State<Reloadable<Option<CorsLayer>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    match cors.get() {
+        Some(cors) => match cors.layer(next).oneshot(req).await {
+            Ok(res) => res,
+            Err(infallible) => match infallible {},
+        },
+        None => next.run(req).await,
+    }
+}
+
+/// Request timeout following the `server.interceptions.timeout_request`
+/// configuration.
+pub fn timeout(watch: &ConfigWatch) -> Reloadable<Option<Duration>> {
+    Reloadable::new(watch, |cfg| {
+        cfg.server
+            .interceptions
+            .timeout_request
+            .as_ref()
+            .filter(|c| c.enable)
+            .map(|c| Duration::from_millis(c.timeout))
+    })
+}
+
+pub async fn timeout_middleware(
+    State(timeout): State<Reloadable<Option<Duration>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    match timeout.get() {
+        Some(timeout) => tokio::time::timeout(timeout, next.run(req))
+            .await
+            .unwrap_or_else(|_| StatusCode::REQUEST_TIMEOUT.into_response()),
+        None => next.run(req).await,
+    }
+}
@@ -1,17 +1,32 @@
 pub mod adapter;
+pub mod cache;
+pub mod cli;
 pub mod config;
 pub mod context;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod database;
 pub mod errors;
+pub mod event;
 pub mod health;
 pub mod hook;
 pub mod interception;
 pub(crate) mod logo;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod prelude;
+pub mod reload;
 pub mod render;
 pub mod responses;
+pub mod scheduler;
+pub mod secret;
+pub mod server;
+pub mod settings;
 pub mod signal;
 pub mod startup;
 pub mod state;
+pub mod testing;
+pub mod timeline;
 pub mod types;
+pub mod worker;
 
 pub type Result<T, E = errors::Error> = std::result::Result<T, E>;
@@ -0,0 +1,404 @@
+//! Prometheus metrics of the application.
+//!
+//! [`MetricsAdapter`] records the HTTP requests and the adapters lifecycle,
+//! and serves every metric of the [`Metrics`] registry in the Prometheus text
+//! format. The registry is available from the context to register the
+//! application metrics.
+//!
+//! Example
+//! ```rust,ignore
+//! use prometheus_client::metrics::counter::Counter;
+//!
+//! let orders = Counter::<u64>::default();
+//! ctx.get::<Metrics>()
+//!     .expect("metrics adapter is registered")
+//!     .register("orders", "Orders placed", orders.clone());
+//! orders.inc();
+//! ```
+use std::{
+    sync::{atomic::AtomicU64, Arc, PoisonError, RwLock},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use http::{header, StatusCode};
+use prometheus_client::{
+    encoding::{text::encode, EncodeLabelSet},
+    metrics::{
+        counter::Counter,
+        family::{Family, MetricConstructor},
+        gauge::Gauge,
+        histogram::Histogram,
+    },
+    registry::{Metric, Registry},
+};
+use serde::Deserialize;
+use utoipa::openapi::{
+    path::{HttpMethod, OperationBuilder, PathItem},
+    OpenApi, OpenApiBuilder, PathsBuilder, ResponseBuilder,
+};
+
+pub use prometheus_client;
+
+use crate::{
+    adapter::{Adapter, AdapterConfig, AdapterPolicy, AdapterStatuses},
+    context::Context,
+    errors::Error,
+    Result,
+};
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Registry of the metrics served by the [`MetricsAdapter`].
+#[derive(Debug, Clone, Default)]
+pub struct Metrics(Arc<RwLock<Registry>>);
+
+impl Metrics {
+    /// Register a metric, the counters name get the `_total` suffix.
+    pub fn register(&self, name: &str, help: &str, metric: impl Metric) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .register(name, help, metric);
+    }
+
+    /// Every registered metric in the text format.
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = String::new();
+        encode(
+            &mut buffer,
+            &self.0.read().unwrap_or_else(PoisonError::into_inner),
+        )
+        .map_err(|e| Error::Message(format!("failed to encode metrics: {e}")))?;
+        Ok(buffer)
+    }
+}
+
+/// Configuration of the [`MetricsAdapter`], bound from `adapters.metrics`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Path serving the metrics
+    pub path: String,
+    /// Buckets in seconds of the request duration histogram
+    pub buckets: Vec<f64>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            path: "/metrics".to_string(),
+            buckets: vec![
+                0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RequestLabels {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RouteLabels {
+    method: String,
+    route: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct AdapterLabels {
+    adapter: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct HookLabels {
+    adapter: String,
+    hook: String,
+}
+
+#[derive(Debug, Clone)]
+struct Buckets(Vec<f64>);
+
+impl MetricConstructor<Histogram> for Buckets {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new(self.0.iter().copied())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HttpMetrics {
+    requests: Family<RequestLabels, Counter>,
+    in_flight: Family<RouteLabels, Gauge>,
+    duration: Family<RequestLabels, Histogram, Buckets>,
+}
+
+/// Lifecycle of the adapters, refreshed from the [`AdapterStatuses`] on each
+/// scrape.
+#[derive(Debug, Clone, Default)]
+struct AdapterMetrics {
+    hook_duration: Family<HookLabels, Gauge<f64, AtomicU64>>,
+    healthy: Family<AdapterLabels, Gauge>,
+}
+
+/// Adapter recording the HTTP requests of the routes mounted before it,
+/// labelled by route template, method and status, and the adapters
+/// lifecycle hooks duration.
+///
+/// The metrics are served at `adapters.metrics.path`, `/metrics` by default.
+///
+/// Example
+/// ```rust,ignore
+/// async fn adapters() -> Result<Vec<Box<dyn Adapter>>> {
+///     Ok(vec![Box::new(MetricsAdapter::default())])
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MetricsAdapter {
+    config: MetricsConfig,
+    metrics: Metrics,
+    http: Option<HttpMetrics>,
+}
+
+impl MetricsAdapter {
+    /// Registry of the adapter, also set in the context by `before_run`.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[async_trait]
+impl Adapter for MetricsAdapter {
+    fn name(&self) -> String {
+        "metrics".to_string()
+    }
+
+    fn policy(&self) -> AdapterPolicy {
+        AdapterPolicy::optional()
+    }
+
+    fn configure(&mut self, config: AdapterConfig<'_>) -> Result<()> {
+        self.config = config.bind()?;
+        Ok(())
+    }
+
+    async fn before_run(&mut self, mut ctx: Context) -> Result<Context> {
+        let http = HttpMetrics {
+            requests: Family::default(),
+            in_flight: Family::default(),
+            duration: Family::new_with_constructor(Buckets(self.config.buckets.clone())),
+        };
+        self.metrics.register(
+            "http_requests",
+            "Number of HTTP requests",
+            http.requests.clone(),
+        );
+        self.metrics.register(
+            "http_requests_in_flight",
+            "Number of HTTP requests in progress",
+            http.in_flight.clone(),
+        );
+        self.metrics.register(
+            "http_request_duration_seconds",
+            "Duration of the HTTP requests in seconds",
+            http.duration.clone(),
+        );
+        self.http = Some(http);
+        ctx.set(self.metrics.clone());
+        Ok(ctx)
+    }
+
+    fn openapi(&self) -> Option<OpenApi> {
+        let operation = OperationBuilder::new()
+            .tag("metrics")
+            .summary(Some("Metrics in the Prometheus text format"))
+            .response("200", ResponseBuilder::new().description("Success"));
+        Some(
+            OpenApiBuilder::new()
+                .paths(
+                    PathsBuilder::new()
+                        .path(&self.config.path, PathItem::new(HttpMethod::Get, operation)),
+                )
+                .build(),
+        )
+    }
+
+    async fn after_route(&self, ctx: &Context, router: Router) -> Result<Router> {
+        let http = self
+            .http
+            .clone()
+            .ok_or_else(|| Error::string("metrics adapter used before `before_run`"))?;
+
+        let adapters = AdapterMetrics::default();
+        self.metrics.register(
+            "adapter_hook_duration_seconds",
+            "Duration of the adapters lifecycle hooks in seconds",
+            adapters.hook_duration.clone(),
+        );
+        self.metrics.register(
+            "adapter_healthy",
+            "Whether the adapter is healthy",
+            adapters.healthy.clone(),
+        );
+        let state = ScrapeState {
+            metrics: self.metrics.clone(),
+            statuses: ctx.get::<AdapterStatuses>().cloned(),
+            adapters,
+        };
+
+        Ok(router
+            .route_layer(axum::middleware::from_fn_with_state(http, track))
+            .route(&self.config.path, get(scrape).with_state(state)))
+    }
+}
+
+/// Decrement the in-flight gauge when the request completes or is dropped.
+struct InFlight(Gauge);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+async fn track(State(http): State<HttpMetrics>, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| req.uri().path().to_string(), |p| p.as_str().to_string());
+
+    let gauge = http
+        .in_flight
+        .get_or_create(&RouteLabels {
+            method: method.clone(),
+            route: route.clone(),
+        })
+        .clone();
+    gauge.inc();
+    let in_flight = InFlight(gauge);
+
+    let started = Instant::now();
+    let response = next.run(req).await;
+    drop(in_flight);
+
+    let labels = RequestLabels {
+        method,
+        route,
+        status: response.status().as_u16(),
+    };
+    http.requests.get_or_create(&labels).inc();
+    http.duration
+        .get_or_create(&labels)
+        .observe(started.elapsed().as_secs_f64());
+    response
+}
+
+#[derive(Clone)]
+struct ScrapeState {
+    metrics: Metrics,
+    statuses: Option<AdapterStatuses>,
+    adapters: AdapterMetrics,
+}
+
+async fn scrape(State(state): State<ScrapeState>) -> Response {
+    for status in state.statuses.iter().flat_map(AdapterStatuses::snapshot) {
+        for (hook, seconds) in &status.timings {
+            state
+                .adapters
+                .hook_duration
+                .get_or_create(&HookLabels {
+                    adapter: status.name.clone(),
+                    hook: hook.clone(),
+                })
+                .set(*seconds);
+        }
+        state
+            .adapters
+            .healthy
+            .get_or_create(&AdapterLabels {
+                adapter: status.name.clone(),
+            })
+            .set(i64::from(status.is_healthy()));
+    }
+
+    match state.metrics.encode() {
+        Ok(body) => ([(header::CONTENT_TYPE, CONTENT_TYPE)], body).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "failed to encode metrics");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AdapterManager;
+    use axum::body::Body;
+    use http::Request;
+    use prometheus_client::metrics::counter::Counter;
+    use tower::ServiceExt;
+
+    async fn send(router: &Router, uri: &str) -> (StatusCode, String) {
+        let response = router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_metrics_adapter() {
+        let mut manager = AdapterManager::new(crate::testing::context_with(|cfg| {
+            cfg.adapters = serde_json::from_value(
+                serde_json::json!({ "metrics": { "path": "/internal/metrics" } }),
+            )
+            .unwrap();
+        }));
+        manager.register(Box::new(MetricsAdapter::default()));
+        manager.init_all().await.unwrap();
+        let ctx = manager.before_run().await.unwrap();
+
+        let orders = Counter::<u64>::default();
+        ctx.get::<Metrics>()
+            .unwrap()
+            .register("orders", "Orders placed", orders.clone());
+        orders.inc();
+
+        let router = Router::new().route("/users/{id}", get(|| async { "user" }));
+        let router = manager.configure_routes(router).await.unwrap();
+        assert_eq!(send(&router, "/users/1").await.0, StatusCode::OK);
+        assert_eq!(send(&router, "/users/2").await.0, StatusCode::OK);
+
+        let (status, body) = send(&router, "/internal/metrics").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body
+            .contains(r#"http_requests_total{method="GET",route="/users/{id}",status="200"} 2"#));
+        assert!(body.contains(r#"http_requests_in_flight{method="GET",route="/users/{id}"} 0"#));
+        assert!(body.contains(
+            r#"http_request_duration_seconds_count{method="GET",route="/users/{id}",status="200"} 2"#
+        ));
+        assert!(body.contains(r#"adapter_hook_duration_seconds{adapter="metrics",hook="init"}"#));
+        assert!(body.contains(r#"adapter_healthy{adapter="metrics"} 1"#));
+        assert!(body.contains("orders_total 1"));
+
+        let doc = manager.openapi().unwrap();
+        assert!(doc.paths.paths.contains_key("/internal/metrics"));
+        assert!(manager.stop_all().await.is_ok());
+    }
+}
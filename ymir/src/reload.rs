@@ -0,0 +1,384 @@
+use std::{
+    future::pending,
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+use tokio::{sync::watch, time::Interval};
+
+use crate::{
+    adapter::AdapterManager,
+    config::{Config, ConfigLoader},
+    context::Context,
+    errors::Error,
+    Result,
+};
+
+/// Current configuration of the application, published again on each
+/// reload.
+///
+/// `Context::configs` keeps the configuration loaded at startup, read the
+/// current one from the `ConfigWatch` of the context.
+#[derive(Debug, Clone)]
+pub struct ConfigWatch(Arc<watch::Sender<Config>>);
+
+impl ConfigWatch {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(watch::Sender::new(config)))
+    }
+
+    pub fn current(&self) -> Config {
+        self.0.borrow().clone()
+    }
+
+    /// Receiver notified of each published configuration.
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.0.subscribe()
+    }
+
+    pub fn publish(&self, config: Config) {
+        self.0.send_replace(config);
+    }
+}
+
+/// Value derived from the configuration, kept up to date on reload.
+///
+/// Example
+/// ```rust,ignore
+/// let level = Reloadable::new(&watch, |cfg| cfg.logger.level.clone());
+/// tracing::info!(level = level.get());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Reloadable<T>(Arc<RwLock<T>>);
+
+impl<T: Clone + Send + Sync + 'static> Reloadable<T> {
+    /// Derive the value with `f` from the current configuration, then from
+    /// every configuration published by `watch`.
+    pub fn new<F>(watch: &ConfigWatch, f: F) -> Self
+    where
+        F: Fn(&Config) -> T + Send + 'static,
+    {
+        let mut rx = watch.subscribe();
+        let value = Arc::new(RwLock::new(f(&rx.borrow_and_update())));
+        let weak = Arc::downgrade(&value);
+        tokio::spawn(async move {
+            while rx.changed().await.is_ok() {
+                let Some(value) = weak.upgrade() else {
+                    break;
+                };
+                let updated = f(&rx.borrow_and_update());
+                *value.write().unwrap_or_else(PoisonError::into_inner) = updated;
+            }
+        });
+        Self(value)
+    }
+
+    pub fn get(&self) -> T {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Check a configuration before it is published, see [`Config::validate`].
+pub fn validate(config: &Config) -> Result<()> {
+    Ok(config.validate()?)
+}
+
+/// Settings only applied when the server starts.
+fn restart_required(current: &Config, new: &Config) -> Vec<&'static str> {
+    fn differs<T: Serialize>(a: &T, b: &T) -> bool {
+        serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+    }
+
+    let (current, new) = (&current.server, &new.server);
+    [
+        (
+            "server.host",
+            current.host != new.host || current.port != new.port,
+        ),
+        ("server.protocol", current.protocol != new.protocol),
+        (
+            "server.listeners",
+            differs(&current.listeners, &new.listeners),
+        ),
+        ("server.tls", differs(&current.tls, &new.tls)),
+        (
+            "server.connection",
+            differs(&current.connection, &new.connection),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(name, changed)| changed.then_some(name))
+    .collect()
+}
+
+/// Load the configuration again, publish it through the [`ConfigWatch`] of
+/// `ctx` and notify the adapters.
+///
+/// Returns `false` when the configuration did not change.
+pub async fn reload(ctx: &Context, adapter_manager: &AdapterManager) -> Result<bool> {
+    let watch = ctx
+        .get::<ConfigWatch>()
+        .ok_or_else(|| Error::string("the context has no ConfigWatch"))?;
+    let environment = ctx
+        .environment
+        .as_ref()
+        .ok_or_else(|| Error::string("the context has no environment"))?;
+
+    let loader = ctx.get::<ConfigLoader>().cloned().unwrap_or_default();
+    let config = loader.load(environment).map_err(Error::wrap)?;
+    validate(&config)?;
+
+    let current = watch.current();
+    // The secrets serialize redacted, they are compared on their own.
+    if current.secret == config.secret
+        && serde_json::to_value(&current)? == serde_json::to_value(&config)?
+    {
+        return Ok(false);
+    }
+    for setting in restart_required(&current, &config) {
+        tracing::warn!(setting, "configuration changed, restart to apply it");
+    }
+
+    watch.publish(config.clone());
+    adapter_manager.notify_config_change(&config).await;
+    tracing::info!("configuration reloaded");
+    Ok(true)
+}
+
+/// Reload the configuration when a file of the `configs` directory changes
+/// or when the process receives SIGHUP, following the `reload`
+/// configuration. Never returns.
+pub async fn watch_config(ctx: &Context, adapter_manager: &AdapterManager) {
+    let Some(settings) = ctx
+        .configs
+        .as_ref()
+        .map(|c| c.reload.clone())
+        .filter(|r| r.enable)
+    else {
+        return pending().await;
+    };
+
+    let dir = ctx
+        .get::<ConfigLoader>()
+        .cloned()
+        .unwrap_or_default()
+        .config_dir();
+    let mut files = modified_at(&dir);
+    let mut interval = (settings.interval > 0)
+        .then(|| tokio::time::interval(Duration::from_millis(settings.interval)));
+    let mut hangup = Hangup::new(settings.signal);
+    tracing::info!(dir = %dir.display(), interval = settings.interval, signal = settings.signal, "watch configuration");
+
+    loop {
+        tokio::select! {
+            () = hangup.recv() => tracing::info!("SIGHUP received, reload configuration"),
+            () = tick(&mut interval) => {
+                let current = modified_at(&dir);
+                if current == files {
+                    continue;
+                }
+                files = current;
+            }
+        }
+        if let Err(e) = reload(ctx, adapter_manager).await {
+            tracing::error!(error = %e, "configuration reload rejected, keep the current one");
+        }
+    }
+}
+
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => pending().await,
+    }
+}
+
+fn modified_at(dir: &PathBuf) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok();
+            (entry.path(), modified)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    #[cfg(unix)]
+    fn new(enable: bool) -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let signal = enable
+            .then(|| signal(SignalKind::hangup()))
+            .and_then(|signal| {
+                signal
+                    .inspect_err(
+                        |e| tracing::error!(error = %e, "failed to install SIGHUP handler"),
+                    )
+                    .ok()
+            });
+        Self { signal }
+    }
+
+    #[cfg(not(unix))]
+    fn new(_enable: bool) -> Self {
+        Self {}
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+        pending().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        adapter::Adapter,
+        config::{load_configuration, Environment, InterceptionTimeoutRequest},
+        interception::interception_fn,
+        secret::Secret,
+    };
+    use async_trait::async_trait;
+    use axum::{body::Body, routing::get, Router};
+    use http::{Request, StatusCode};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tower::ServiceExt;
+
+    fn context(config: Config) -> Context {
+        let mut ctx = Context {
+            environment: Some(Environment::Development),
+            configs: Some(config.clone()),
+            extend: Some(Box::default()),
+        };
+        ctx.set(ConfigWatch::new(config));
+        ctx
+    }
+
+    #[derive(Debug, Default)]
+    struct ReloadAdapter {
+        changed: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Adapter for ReloadAdapter {
+        fn name(&self) -> String {
+            "reload".to_string()
+        }
+
+        async fn on_config_change(&self, _ctx: &Context, config: &Config) -> Result<()> {
+            assert_eq!(config.logger.level, "debug");
+            self.changed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let mut config = load_configuration(&Environment::Development).unwrap();
+        let ctx = context(config.clone());
+        let manager = AdapterManager::new(ctx.clone());
+        assert!(!reload(&ctx, &manager).await.unwrap());
+
+        // The configuration on disk differs from the published one.
+        config.logger.level = "trace".to_string();
+        let ctx = context(config);
+        let adapter = ReloadAdapter::default();
+        let changed = adapter.changed.clone();
+        let mut manager = AdapterManager::new(ctx.clone());
+        manager.register(Box::new(adapter));
+
+        let level = Reloadable::new(ctx.get::<ConfigWatch>().unwrap(), |cfg| {
+            cfg.logger.level.clone()
+        });
+        assert_eq!(level.get(), "trace");
+        assert!(reload(&ctx, &manager).await.unwrap());
+        assert!(changed.load(Ordering::SeqCst));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(level.get(), "debug");
+    }
+
+    #[tokio::test]
+    async fn test_reload_secret() {
+        let mut config = load_configuration(&Environment::Development).unwrap();
+        config.secret.cookie = Secret::new("rotated".to_string());
+        let ctx = context(config);
+        let manager = AdapterManager::new(ctx.clone());
+
+        assert!(reload(&ctx, &manager).await.unwrap());
+        let watch = ctx.get::<ConfigWatch>().unwrap();
+        assert_ne!(watch.current().secret.cookie.expose(), "rotated");
+        assert!(!reload(&ctx, &manager).await.unwrap());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = load_configuration(&Environment::Development).unwrap();
+        assert!(validate(&config).is_ok());
+
+        let cors = config.server.interceptions.cors.as_mut().unwrap();
+        cors.enable = true;
+        cors.allow_methods = Some(vec!["NOT A METHOD".to_string()]);
+        assert_eq!(
+            validate(&config).unwrap_err().to_string(),
+            "invalid configuration, 1 problem(s):\n  \
+             server.interceptions.cors.allow_methods[0]: invalid method \"NOT A METHOD\": invalid HTTP method"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reloadable_timeout_interception() {
+        let mut config = load_configuration(&Environment::Development).unwrap();
+        config.reload.enable = true;
+        config.server.interceptions.timeout_request = None;
+        let ctx = context(config.clone());
+
+        let router = interception_fn(
+            ctx.clone(),
+            Router::new().route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    "done"
+                }),
+            ),
+        )
+        .unwrap();
+        let slow = || {
+            router
+                .clone()
+                .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+        };
+        assert_eq!(slow().await.unwrap().status(), StatusCode::OK);
+
+        config.server.interceptions.timeout_request = Some(InterceptionTimeoutRequest {
+            enable: true,
+            timeout: 10,
+        });
+        ctx.get::<ConfigWatch>().unwrap().publish(config);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(slow().await.unwrap().status(), StatusCode::REQUEST_TIMEOUT);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{fmt::Debug, future::Future, str::FromStr, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use tokio::{
+    task::JoinHandle,
+    time::{Instant, Interval, MissedTickBehavior},
+};
+use tracing::Instrument;
+
+use crate::{
+    config::SchedulerJob,
+    context::Context,
+    errors::Error,
+    worker::{Shutdown, Worker, WorkerFuture},
+    Result,
+};
+
+type JobFn = Arc<dyn Fn(Context) -> WorkerFuture + Send + Sync>;
+
+/// When a job runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// Cron expression, `sec min hour day month weekday [year]` or the five
+    /// fields form without seconds, evaluated in UTC.
+    Cron(String),
+    /// Fixed delay between two runs, the first run happens after one period.
+    Interval(Duration),
+}
+
+impl Schedule {
+    /// Schedule configured in `scheduler.jobs.<name>`.
+    fn from_config(name: &str, config: &SchedulerJob) -> Result<Option<Self>> {
+        match (&config.cron, config.interval) {
+            (Some(_), Some(_)) => Err(Error::Message(format!(
+                "job `{name}`: `cron` and `interval` are mutually exclusive"
+            ))),
+            (Some(cron), None) => Ok(Some(Self::Cron(cron.clone()))),
+            (None, Some(interval)) => Ok(Some(Self::Interval(Duration::from_millis(interval)))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn timer(&self, name: &str) -> Result<Timer> {
+        match self {
+            Self::Cron(expression) => parse_cron(expression)
+                .map(|schedule| Timer::Cron(Box::new(schedule)))
+                .map_err(|e| Error::Message(format!("job `{name}`: invalid cron expression: {e}"))),
+            Self::Interval(period) if period.is_zero() => Err(Error::Message(format!(
+                "job `{name}`: interval must be greater than zero"
+            ))),
+            Self::Interval(period) => {
+                let mut interval = tokio::time::interval_at(Instant::now() + *period, *period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                Ok(Timer::Interval(interval))
+            }
+        }
+    }
+}
+
+/// Parse a cron `expression`, with or without the seconds field.
+pub(crate) fn parse_cron(
+    expression: &str,
+) -> std::result::Result<cron::Schedule, cron::error::Error> {
+    // The cron crate requires the seconds field.
+    if expression.split_whitespace().count() == 5 {
+        cron::Schedule::from_str(&format!("0 {expression}"))
+    } else {
+        cron::Schedule::from_str(expression)
+    }
+}
+
+enum Timer {
+    Cron(Box<cron::Schedule>),
+    Interval(Interval),
+}
+
+impl Timer {
+    /// Wait for the next run, returns `false` when the schedule has no
+    /// upcoming run.
+    async fn tick(&mut self) -> bool {
+        match self {
+            Self::Cron(schedule) => {
+                let Some(next) = schedule.upcoming(Utc).next() else {
+                    return false;
+                };
+                let delay = (next - Utc::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(delay).await;
+                true
+            }
+            Self::Interval(interval) => {
+                interval.tick().await;
+                true
+            }
+        }
+    }
+}
+
+/// A named job run periodically next to the HTTP server.
+///
+/// The schedule given in code is a default, `scheduler.jobs.<name>` in the
+/// configuration overrides it or disables the job.
+///
+/// Example
+/// ```rust
+/// use std::time::Duration;
+/// use ymir::scheduler::{Job, Schedule};
+///
+/// let job = Job::new("cleanup", |_ctx| async move {
+///     // remove expired sessions
+///     Ok(())
+/// })
+/// .schedule(Schedule::Cron("0 */5 * * * *".to_string()));
+/// assert_eq!(job.name(), "cleanup");
+/// ```
+#[derive(Clone)]
+pub struct Job {
+    name: String,
+    schedule: Option<Schedule>,
+    run: JobFn,
+}
+
+impl Debug for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Job")
+            .field("name", &self.name)
+            .field("schedule", &self.schedule)
+            .finish()
+    }
+}
+
+impl Job {
+    pub fn new<F, Fut>(name: impl Into<String>, run: F) -> Self
+    where
+        F: Fn(Context) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            schedule: None,
+            run: Arc::new(move |ctx| Box::pin(run(ctx))),
+        }
+    }
+
+    /// Set the default schedule of the job.
+    #[must_use]
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Turn the jobs into workers following the `scheduler` configuration
+/// of `ctx`, disabled jobs are skipped.
+///
+/// A run is skipped while the previous one is still in progress, and the
+/// in-flight run is awaited when the application shuts down.
+pub fn workers(ctx: &Context, jobs: Vec<Job>) -> Result<Vec<Worker>> {
+    let configs = ctx
+        .configs
+        .as_ref()
+        .map(|c| c.scheduler.jobs.clone())
+        .unwrap_or_default();
+
+    let mut workers = vec![];
+    for job in jobs {
+        let config = configs.get(&job.name);
+        if config.is_some_and(|c| !c.enable) {
+            tracing::info!(job = job.name, "job disabled");
+            continue;
+        }
+        let schedule = match config {
+            Some(config) => Schedule::from_config(&job.name, config)?,
+            None => None,
+        }
+        .or_else(|| job.schedule.clone())
+        .ok_or_else(|| Error::Message(format!("job `{}` has no schedule", job.name)))?;
+        // Validate the schedule before the application starts.
+        schedule.timer(&job.name)?;
+
+        let job = Arc::new(job);
+        workers.push(Worker::new(
+            format!("job:{}", job.name),
+            move |ctx, shutdown| run(ctx, job.clone(), schedule.clone(), shutdown),
+        ));
+    }
+    Ok(workers)
+}
+
+async fn run(
+    ctx: Context,
+    job: Arc<Job>,
+    schedule: Schedule,
+    mut shutdown: Shutdown,
+) -> Result<()> {
+    let mut timer = schedule.timer(&job.name)?;
+    tracing::info!(job = job.name, ?schedule, "job scheduled");
+    let mut running: Option<JoinHandle<()>> = None;
+
+    loop {
+        tokio::select! {
+            _ = shutdown.recv() => break,
+            ticked = timer.tick() => if !ticked {
+                tracing::info!(job = job.name, "job has no upcoming run");
+                break;
+            },
+        }
+        if running.as_ref().is_some_and(|run| !run.is_finished()) {
+            tracing::warn!(job = job.name, "previous run still in progress, skip run");
+            continue;
+        }
+        running = Some(tokio::spawn(execute(ctx.clone(), job.clone())));
+    }
+
+    if let Some(running) = running.filter(|run| !run.is_finished()) {
+        tracing::info!(job = job.name, "wait for in-flight run");
+        let _ = running.await;
+    }
+    Ok(())
+}
+
+async fn execute(ctx: Context, job: Arc<Job>) {
+    let span = tracing::info_span!("job", job = job.name, run = %ulid::Ulid::new());
+    async move {
+        let started = Instant::now();
+        tracing::info!("job started");
+        // Run on its own task so a panic is reported as a failure.
+        let result = match tokio::spawn((job.run)(ctx)).await {
+            Ok(result) => result,
+            Err(e) => Err(Error::Message(format!("job panicked: {e}"))),
+        };
+        let elapsed_ms = started.elapsed().as_millis();
+        match result {
+            Ok(()) => tracing::info!(elapsed_ms, "job finished"),
+            Err(e) => tracing::error!(elapsed_ms, error = %e, "job failed"),
+        }
+    }
+    .instrument(span)
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{load_configuration, Environment},
+        worker::Supervisor,
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::sync::broadcast;
+
+    fn context(jobs: &[(&str, SchedulerJob)]) -> Context {
+        let mut configs = load_configuration(&Environment::Development).unwrap();
+        for (name, job) in jobs {
+            configs.scheduler.jobs.insert(name.to_string(), job.clone());
+        }
+        Context {
+            environment: Some(Environment::Development),
+            configs: Some(configs),
+            extend: Some(Box::default()),
+        }
+    }
+
+    fn counting_job(name: &str, delay: Duration) -> (Job, Arc<AtomicU32>) {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        let job = Job::new(name, move |_ctx| {
+            let counter = counter.clone();
+            async move {
+                tokio::time::sleep(delay).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        (job, runs)
+    }
+
+    #[test]
+    fn test_schedule_from_config() {
+        let ctx = context(&[
+            (
+                "both",
+                SchedulerJob {
+                    enable: true,
+                    cron: Some("* * * * * *".to_string()),
+                    interval: Some(10),
+                },
+            ),
+            (
+                "invalid",
+                SchedulerJob {
+                    enable: true,
+                    cron: Some("every minute".to_string()),
+                    interval: None,
+                },
+            ),
+            (
+                "disabled",
+                SchedulerJob {
+                    enable: false,
+                    cron: None,
+                    interval: None,
+                },
+            ),
+        ]);
+        let job = |name: &str| Job::new(name, |_ctx| async { Ok(()) });
+
+        assert!(workers(&ctx, vec![job("both")]).is_err());
+        assert!(workers(&ctx, vec![job("invalid")]).is_err());
+        assert!(workers(&ctx, vec![job("unscheduled")]).is_err());
+        assert!(workers(&ctx, vec![job("disabled")]).unwrap().is_empty());
+
+        let five_fields = job("five").schedule(Schedule::Cron("*/5 * * * *".to_string()));
+        let workers = workers(&ctx, vec![five_fields]).unwrap();
+        assert_eq!(workers[0].name(), "job:five");
+    }
+
+    #[tokio::test]
+    async fn test_config_overrides_schedule() {
+        let ctx = context(&[(
+            "tick",
+            SchedulerJob {
+                enable: true,
+                cron: None,
+                interval: Some(10),
+            },
+        )]);
+        let (job, runs) = counting_job("tick", Duration::ZERO);
+        let job = job.schedule(Schedule::Interval(Duration::from_secs(3600)));
+
+        let (tx, _) = broadcast::channel(1);
+        let workers = workers(&ctx, vec![job]).unwrap();
+        let supervisor = Supervisor::start(ctx, workers, tx.subscribe());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        tx.send(()).unwrap();
+        supervisor.join().await;
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_skip_overlapping_runs_and_wait_in_flight() {
+        let ctx = context(&[]);
+        let (job, runs) = counting_job("slow", Duration::from_millis(150));
+        let job = job.schedule(Schedule::Interval(Duration::from_millis(10)));
+
+        let (tx, _) = broadcast::channel(1);
+        let workers = workers(&ctx, vec![job]).unwrap();
+        let supervisor = Supervisor::start(ctx, workers, tx.subscribe());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        tx.send(()).unwrap();
+        supervisor.join().await;
+        // The single run started before the shutdown completed.
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
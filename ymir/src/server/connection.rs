@@ -0,0 +1,172 @@
+use std::{sync::Arc, time::Duration};
+
+use hyper::server::conn::http1;
+use hyper_util::{
+    rt::{TokioExecutor, TokioTimer},
+    server::conn::auto,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::ServerConnection;
+
+/// Connection settings shared by the listeners of a server.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionOptions {
+    config: ServerConnection,
+    permits: Option<Arc<Semaphore>>,
+}
+
+impl ConnectionOptions {
+    /// Options applying `config`, the `max_connections` cap is shared by
+    /// every clone.
+    pub fn new(config: ServerConnection) -> Self {
+        let permits = config
+            .max_connections
+            .map(|max| Arc::new(Semaphore::new(max)));
+        Self { config, permits }
+    }
+
+    pub fn tcp_nodelay(&self) -> bool {
+        self.config.tcp_nodelay
+    }
+
+    /// Wait for a connection slot, `None` when connections are not capped.
+    pub(crate) async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.permits {
+            Some(permits) => permits.clone().acquire_owned().await.ok(),
+            None => None,
+        }
+    }
+
+    /// Build the hyper connection builder, `tls` connections negotiate
+    /// HTTP/2 with ALPN while plain connections only accept it with `h2c`.
+    pub(crate) fn builder(&self, tls: bool) -> HttpBuilder {
+        let http1 = &self.config.http1;
+        if !tls && !self.config.h2c {
+            let mut builder = http1::Builder::new();
+            builder
+                .timer(TokioTimer::new())
+                .keep_alive(http1.keep_alive)
+                .header_read_timeout(http1.header_read_timeout.map(Duration::from_millis));
+            return HttpBuilder::Http1(builder);
+        }
+
+        let mut builder = auto::Builder::new(TokioExecutor::new());
+        builder
+            .http1()
+            .timer(TokioTimer::new())
+            .keep_alive(http1.keep_alive)
+            .header_read_timeout(http1.header_read_timeout.map(Duration::from_millis));
+
+        let http2 = &self.config.http2;
+        let mut h2 = builder.http2();
+        h2.timer(TokioTimer::new())
+            .max_concurrent_streams(http2.max_concurrent_streams)
+            .keep_alive_interval(http2.keep_alive_interval.map(Duration::from_millis))
+            .initial_stream_window_size(http2.initial_stream_window_size)
+            .initial_connection_window_size(http2.initial_connection_window_size)
+            .max_frame_size(http2.max_frame_size);
+        if let Some(timeout) = http2.keep_alive_timeout {
+            h2.keep_alive_timeout(Duration::from_millis(timeout));
+        }
+
+        HttpBuilder::Auto(builder)
+    }
+}
+
+/// Builder of the connections of a listener.
+#[derive(Clone)]
+pub(crate) enum HttpBuilder {
+    /// HTTP/1 only
+    Http1(http1::Builder),
+    /// HTTP/1 or HTTP/2, detected from the connection preface
+    Auto(auto::Builder<TokioExecutor>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{serve_listener, tests::http_get};
+    use axum::{routing::get, Router};
+    use std::net::SocketAddr;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::oneshot,
+    };
+
+    const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+    async fn start(config: ServerConnection) -> (SocketAddr, oneshot::Sender<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let (tx, rx) = oneshot::channel::<()>();
+        tokio::spawn(serve_listener(
+            listener,
+            app,
+            None,
+            ConnectionOptions::new(config),
+            async {
+                let _ = rx.await;
+            },
+        ));
+        (addr, tx)
+    }
+
+    /// Send the HTTP/2 connection preface, returns `true` when the server
+    /// answers with a SETTINGS frame.
+    async fn h2_handshake(addr: SocketAddr) -> bool {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(H2_PREFACE).await.unwrap();
+        let mut frame = Vec::new();
+        let _ = (&mut stream).take(9).read_to_end(&mut frame).await;
+        frame.len() == 9 && frame[3] == 0x4
+    }
+
+    #[tokio::test]
+    async fn test_h2c() {
+        let (addr, _stop) = start(ServerConnection::default()).await;
+        assert!(!h2_handshake(addr).await);
+
+        let (addr, _stop) = start(ServerConnection {
+            h2c: true,
+            ..Default::default()
+        })
+        .await;
+        assert!(h2_handshake(addr).await);
+    }
+
+    #[tokio::test]
+    async fn test_max_connections() {
+        let (addr, _stop) = start(ServerConnection {
+            max_connections: Some(1),
+            ..Default::default()
+        })
+        .await;
+
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0; 1024];
+        let n = first.read(&mut buf).await.unwrap();
+        assert!(buf[..n].starts_with(b"HTTP/1.1 200 OK"));
+
+        // The second connection waits until the first one is closed.
+        let second =
+            tokio::spawn(
+                async move { http_get(TcpStream::connect(addr).await.unwrap(), "/").await },
+            );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!second.is_finished());
+
+        drop(first);
+        let response = tokio::time::timeout(Duration::from_secs(1), second)
+            .await
+            .expect("second connection was not served")
+            .unwrap();
+        assert!(response.ends_with("ok"));
+    }
+}
@@ -0,0 +1,591 @@
+pub mod connection;
+pub mod tls;
+#[cfg(unix)]
+pub mod unix;
+
+use std::{fmt::Debug, future::Future, io, net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    response::{IntoResponse, Redirect},
+    Router,
+};
+use http::{header::HOST, uri::Authority, HeaderMap, Request, StatusCode, Uri};
+use hyper::body::Incoming;
+use hyper_util::{rt::TokioIo, service::TowerToHyperService};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    task::JoinSet,
+};
+use tower::ServiceExt;
+
+use crate::{
+    config::{ListenerBind, ListenerRouter, ServerListener},
+    context::Context,
+    errors::Error,
+    signal,
+    timeline::StartupTimeline,
+    Result,
+};
+use connection::{ConnectionOptions, HttpBuilder};
+use tls::TlsAcceptor;
+
+/// Maximum time allowed to complete a TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve `app` following the `server` configuration until
+/// [`signal::drain_signal`] resolves.
+///
+/// Every listener of `server.listeners` serves either `app` or `admin`, they
+/// all share the same graceful shutdown and `server.connection` settings.
+/// When `protocol` is `https` and `server.tls` is configured the TCP
+/// listeners of `app` are served over TLS, and `server.tls.redirect_port`
+/// starts a plain HTTP listener redirecting to HTTPS.
+pub async fn serve(ctx: &Context, app: Router, admin: Option<Router>) -> Result<()> {
+    let config = ctx.configs.clone().expect("load configuration failed.");
+    let server = config.server;
+    let timeline = ctx.get::<StartupTimeline>().cloned().unwrap_or_default();
+
+    let tls = match (server.protocol.as_str(), &server.tls) {
+        ("https", Some(tls)) => Some(TlsAcceptor::from_config(tls)?),
+        ("https", None) => {
+            tracing::warn!("protocol is https but `server.tls` is not configured, serving http");
+            None
+        }
+        _ => None,
+    };
+
+    let connection = ConnectionOptions::new(server.connection.clone());
+    let listeners = server.listeners();
+    let https_port = https_port(&listeners);
+    if admin.is_some() && !listeners.iter().any(|l| l.router == ListenerRouter::Admin) {
+        tracing::warn!("admin routes are not served, no listener uses the admin router");
+    }
+
+    let mut servers = JoinSet::new();
+    for listener in listeners {
+        let (router, tls) = match listener.router {
+            ListenerRouter::Main => (app.clone(), tls.clone()),
+            ListenerRouter::Admin => (
+                admin.clone().ok_or_else(|| {
+                    Error::string("an admin listener is configured without admin routes")
+                })?,
+                None,
+            ),
+        };
+        let signal = signal::drain_signal(ctx);
+
+        match listener.bind {
+            ListenerBind::Tcp { host, port } => {
+                let address = format!("{host}:{port}");
+                let listener = timeline
+                    .step_async(&format!("bind {address}"), TcpListener::bind(&address))
+                    .await?;
+                tracing::info!(
+                    "Listening on {}://{}",
+                    if tls.is_some() { "https" } else { "http" },
+                    &address
+                );
+                servers.spawn(serve_listener(
+                    listener,
+                    router,
+                    tls,
+                    connection.clone(),
+                    signal,
+                ));
+            }
+            #[cfg(unix)]
+            ListenerBind::Unix { path, mode } => {
+                let listener =
+                    timeline.step(&format!("bind unix:{path}"), || unix::bind(&path, mode))?;
+                tracing::info!("Listening on unix:{}", &path);
+                let connection = connection.clone();
+                servers.spawn(async move {
+                    let served = serve_listener(listener, router, None, connection, signal).await;
+                    let _ = std::fs::remove_file(&path);
+                    served
+                });
+            }
+            #[cfg(not(unix))]
+            ListenerBind::Unix { .. } => {
+                return Err(Error::string(
+                    "unix socket listeners are not supported on this platform",
+                ));
+            }
+        }
+    }
+
+    let redirect_port = server
+        .tls
+        .as_ref()
+        .and_then(|t| t.redirect_port)
+        .filter(|_| tls.is_some());
+    if let (Some(port), None) = (redirect_port, https_port) {
+        tracing::warn!(
+            port,
+            "no TCP listener serves the main router, HTTPS redirect disabled"
+        );
+    }
+    if let (Some(port), Some(https_port)) = (redirect_port, https_port) {
+        let address = format!("{}:{}", server.host, port);
+        let listener = timeline
+            .step_async(&format!("bind {address}"), TcpListener::bind(&address))
+            .await?;
+        tracing::info!("Redirecting http://{} to https", &address);
+        servers.spawn(serve_listener(
+            listener,
+            redirect_router(https_port),
+            None,
+            connection,
+            signal::drain_signal(ctx),
+        ));
+    }
+
+    timeline.finish();
+
+    // Dropping the set aborts the other listeners when one of them fails.
+    while let Some(served) = servers.join_next().await {
+        served.map_err(|e| Error::Message(e.to_string()))??;
+    }
+    Ok(())
+}
+
+/// Source of connections served by [`serve_listener`].
+pub trait Listener: Send + 'static {
+    type Io: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+    /// Peer address, inserted in the requests as [`ConnectInfo`].
+    type Addr: Clone + Debug + Send + Sync + 'static;
+
+    fn accept(&mut self) -> impl Future<Output = io::Result<(Self::Io, Self::Addr)>> + Send;
+
+    /// Apply the connection options to an accepted connection.
+    fn configure(_io: &Self::Io, _options: &ConnectionOptions) {}
+}
+
+impl Listener for TcpListener {
+    type Io = TcpStream;
+    type Addr = SocketAddr;
+
+    fn accept(&mut self) -> impl Future<Output = io::Result<(Self::Io, Self::Addr)>> + Send {
+        TcpListener::accept(self)
+    }
+
+    fn configure(io: &Self::Io, options: &ConnectionOptions) {
+        if options.tcp_nodelay() {
+            if let Err(e) = io.set_nodelay(true) {
+                tracing::trace!(error = %e, "failed to set TCP_NODELAY");
+            }
+        }
+    }
+}
+
+/// Accept connections on `listener` until `signal` resolves, then wait for
+/// the open connections to finish their in-flight requests.
+///
+/// The connections are owned by the returned future, dropping it, e.g. once
+/// the drain window expires, aborts the ones still open.
+///
+/// No connection is accepted while the `max_connections` cap of `options`
+/// is reached.
+pub async fn serve_listener<L, F>(
+    mut listener: L,
+    app: Router,
+    tls: Option<TlsAcceptor>,
+    options: ConnectionOptions,
+    signal: F,
+) -> io::Result<()>
+where
+    L: Listener,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (signal_tx, signal_rx) = watch::channel(());
+    let signal_tx = Arc::new(signal_tx);
+    tokio::spawn(async move {
+        signal.await;
+        drop(signal_rx);
+    });
+
+    let builder = options.builder(tls.is_some());
+    let mut connections = JoinSet::new();
+
+    loop {
+        // Release the finished connections.
+        while connections.try_join_next().is_some() {}
+        let permit = tokio::select! {
+            permit = options.acquire() => permit,
+            _ = signal_tx.closed() => break,
+        };
+        let (stream, remote_addr) = tokio::select! {
+            conn = accept(&mut listener) => match conn {
+                Some(conn) => conn,
+                None => continue,
+            },
+            _ = signal_tx.closed() => break,
+        };
+
+        L::configure(&stream, &options);
+
+        let app = app.clone();
+        let tls = tls.clone();
+        let builder = builder.clone();
+        let signal_tx = Arc::clone(&signal_tx);
+
+        connections.spawn(async move {
+            match tls {
+                Some(tls) => {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            serve_connection(&builder, stream, app, remote_addr, &signal_tx).await;
+                        }
+                        Ok(Err(e)) => {
+                            tracing::debug!(?remote_addr, error = %e, "tls handshake failed");
+                        }
+                        Err(_) => tracing::debug!(?remote_addr, "tls handshake timed out"),
+                    }
+                }
+                None => serve_connection(&builder, stream, app, remote_addr, &signal_tx).await,
+            }
+            drop(permit);
+        });
+    }
+
+    drop(listener);
+    while connections.join_next().await.is_some() {}
+    Ok(())
+}
+
+async fn serve_connection<IO, A>(
+    builder: &HttpBuilder,
+    io: IO,
+    app: Router,
+    remote_addr: A,
+    signal_tx: &watch::Sender<()>,
+) where
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    A: Clone + Debug + Send + Sync + 'static,
+{
+    let addr = remote_addr.clone();
+    let service = app.map_request(move |mut req: Request<Incoming>| {
+        req.extensions_mut().insert(ConnectInfo(addr.clone()));
+        req.map(Body::new)
+    });
+    let io = TokioIo::new(io);
+    let service = TowerToHyperService::new(service);
+
+    let result = match builder {
+        HttpBuilder::Http1(builder) => {
+            let conn = builder.serve_connection(io, service).with_upgrades();
+            drive(conn, |conn| conn.graceful_shutdown(), signal_tx)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        HttpBuilder::Auto(builder) => {
+            let conn = builder.serve_connection_with_upgrades(io, service);
+            drive(conn, |conn| conn.graceful_shutdown(), signal_tx)
+                .await
+                .map_err(|e| e.to_string())
+        }
+    };
+    if let Err(e) = result {
+        tracing::trace!(?remote_addr, error = %e, "failed to serve connection");
+    }
+}
+
+/// Wait for `conn` to complete, shutting it down gracefully once the
+/// signal is received.
+async fn drive<C, E>(
+    conn: C,
+    graceful_shutdown: impl Fn(Pin<&mut C>),
+    signal_tx: &watch::Sender<()>,
+) -> std::result::Result<(), E>
+where
+    C: Future<Output = std::result::Result<(), E>>,
+{
+    tokio::pin!(conn);
+    let signal_closed = signal_tx.closed();
+    tokio::pin!(signal_closed);
+    let mut draining = false;
+
+    loop {
+        tokio::select! {
+            result = conn.as_mut() => return result,
+            () = &mut signal_closed, if !draining => {
+                draining = true;
+                graceful_shutdown(conn.as_mut());
+            }
+        }
+    }
+}
+
+async fn accept<L: Listener>(listener: &mut L) -> Option<(L::Io, L::Addr)> {
+    match listener.accept().await {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            if !matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::ConnectionReset
+            ) {
+                // Errors like too many open files, wait before accepting again.
+                tracing::error!(error = %e, "accept error");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            None
+        }
+    }
+}
+
+/// Port of the first TCP listener serving the main router, the one HTTP is
+/// redirected to.
+fn https_port(listeners: &[ServerListener]) -> Option<u16> {
+    listeners.iter().find_map(|listener| match listener {
+        ServerListener {
+            bind: ListenerBind::Tcp { port, .. },
+            router: ListenerRouter::Main,
+        } => Some(*port),
+        _ => None,
+    })
+}
+
+/// Router redirecting every request to the same host and path over HTTPS.
+pub fn redirect_router(https_port: u16) -> Router {
+    Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+        let host = headers
+            .get(HOST)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.parse::<Authority>().ok());
+        let Some(host) = host else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        let port = if https_port == 443 {
+            String::new()
+        } else {
+            format!(":{https_port}")
+        };
+        let path = uri.path_and_query().map_or("/", |p| p.as_str());
+        Redirect::permanent(&format!("https://{}{port}{path}", host.host())).into_response()
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Send a `GET` request over `io` and return the raw response.
+    pub(crate) async fn http_get<IO>(mut io: IO, path: &str) -> String
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+    {
+        let request =
+            format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        io.write_all(request.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        let _ = io.read_to_end(&mut response).await;
+        String::from_utf8_lossy(&response).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_serve_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/ip",
+            get(|ConnectInfo(addr): ConnectInfo<SocketAddr>| async move { addr.ip().to_string() }),
+        );
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_listener(
+            listener,
+            app,
+            None,
+            ConnectionOptions::default(),
+            async {
+                let _ = rx.await;
+            },
+        ));
+
+        let response = http_get(TcpStream::connect(addr).await.unwrap(), "/ip").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("127.0.0.1"));
+
+        tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), server)
+            .await
+            .expect("server did not stop")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drain_timeout_aborts_connections() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use tokio::sync::Notify;
+
+        struct Aborted(Arc<AtomicBool>);
+
+        impl Drop for Aborted {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let started = Arc::new(Notify::new());
+        let aborted = Arc::new(AtomicBool::new(false));
+        let app = Router::new().route(
+            "/slow",
+            get({
+                let started = started.clone();
+                let aborted = aborted.clone();
+                move || async move {
+                    let _aborted = Aborted(aborted);
+                    started.notify_one();
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    "done"
+                }
+            }),
+        );
+        let client = tokio::spawn(async move {
+            http_get(TcpStream::connect(addr).await.unwrap(), "/slow").await
+        });
+        // Drain once the request is in flight, the window expires before it
+        // completes and the listener is dropped.
+        let served = serve_listener(
+            listener,
+            app,
+            None,
+            ConnectionOptions::default(),
+            async move {
+                started.notified().await;
+            },
+        );
+        assert!(tokio::time::timeout(Duration::from_millis(500), served)
+            .await
+            .is_err());
+
+        let response = tokio::time::timeout(Duration::from_secs(1), client)
+            .await
+            .expect("connection was not closed")
+            .unwrap();
+        assert!(response.is_empty());
+        assert!(aborted.load(Ordering::SeqCst));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_unix_listeners() {
+        use crate::{
+            config::{load_configuration, Environment, ServerListener},
+            signal::Drain,
+        };
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::net::UnixStream;
+
+        let dir = std::env::temp_dir().join(format!("ymir-unix-{}", ulid::Ulid::new()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.sock").to_string_lossy().to_string();
+        let admin = dir.join("admin.sock").to_string_lossy().to_string();
+
+        let mut configs = load_configuration(&Environment::Development).unwrap();
+        configs.server.listeners = vec![
+            ServerListener {
+                bind: ListenerBind::Unix {
+                    path: main.clone(),
+                    mode: Some(0o660),
+                },
+                router: ListenerRouter::Main,
+            },
+            ServerListener {
+                bind: ListenerBind::Unix {
+                    path: admin.clone(),
+                    mode: None,
+                },
+                router: ListenerRouter::Admin,
+            },
+        ];
+        let mut ctx = Context {
+            environment: Some(Environment::Development),
+            configs: Some(configs),
+            extend: Some(Box::default()),
+        };
+        let drain = Drain::default();
+        ctx.set(drain.clone());
+
+        let app = Router::new().route("/", get(|| async { "main" }));
+        let admin_app = Router::new().route("/", get(|| async { "admin" }));
+        let server = tokio::spawn(async move { serve(&ctx, app, Some(admin_app)).await });
+        while !std::path::Path::new(&admin).exists() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let mode = std::fs::metadata(&main).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+        let response = http_get(UnixStream::connect(&main).await.unwrap(), "/").await;
+        assert!(response.ends_with("main"));
+        let response = http_get(UnixStream::connect(&admin).await.unwrap(), "/").await;
+        assert!(response.ends_with("admin"));
+
+        drain.trigger();
+        tokio::time::timeout(Duration::from_secs(1), server)
+            .await
+            .expect("server did not stop")
+            .unwrap()
+            .unwrap();
+        assert!(!std::path::Path::new(&main).exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_https_port() {
+        let listener = |bind, router| ServerListener { bind, router };
+        let tcp = |port| ListenerBind::Tcp {
+            host: "0.0.0.0".to_string(),
+            port,
+        };
+        let unix = ListenerBind::Unix {
+            path: "/tmp/app.sock".to_string(),
+            mode: None,
+        };
+        assert_eq!(
+            https_port(&[
+                listener(unix.clone(), ListenerRouter::Main),
+                listener(tcp(9090), ListenerRouter::Admin),
+                listener(tcp(8443), ListenerRouter::Main),
+            ]),
+            Some(8443)
+        );
+        assert_eq!(https_port(&[listener(unix, ListenerRouter::Main)]), None);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_router() {
+        let redirect = |port| {
+            redirect_router(port).oneshot(
+                Request::get("/users?page=2")
+                    .header(HOST, "example.com:8080")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = redirect(8443).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()["location"],
+            "https://example.com:8443/users?page=2"
+        );
+
+        let response = redirect(443).await.unwrap();
+        assert_eq!(
+            response.headers()["location"],
+            "https://example.com/users?page=2"
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    sync::{Arc, PoisonError, RwLock, Weak},
+    time::{Duration, SystemTime},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{CertificateDer, PrivateKeyDer},
+        server::WebPkiClientVerifier,
+        RootCertStore, ServerConfig,
+    },
+    server::TlsStream,
+};
+
+use crate::{config::ServerTls, errors::Error, Result};
+
+type SharedConfig = RwLock<Arc<ServerConfig>>;
+
+/// TLS acceptor reloading its certificates when their files change on disk.
+#[derive(Clone)]
+pub struct TlsAcceptor {
+    config: Arc<SharedConfig>,
+}
+
+impl TlsAcceptor {
+    /// Load the certificates, they are watched for changes every
+    /// `reload_interval` milliseconds unless it is `0`.
+    pub fn from_config(tls: &ServerTls) -> Result<Self> {
+        let acceptor = Self {
+            config: Arc::new(RwLock::new(Arc::new(server_config(tls)?))),
+        };
+        if tls.reload_interval > 0 {
+            tokio::spawn(watch(Arc::downgrade(&acceptor.config), tls.clone()));
+        }
+        Ok(acceptor)
+    }
+
+    /// Perform the TLS handshake with the current certificates.
+    pub async fn accept<IO>(&self, io: IO) -> io::Result<TlsStream<IO>>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+    {
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        tokio_rustls::TlsAcceptor::from(config).accept(io).await
+    }
+}
+
+/// Reload the certificates when one of the files is modified, until the
+/// acceptor is dropped.
+async fn watch(config: Weak<SharedConfig>, tls: ServerTls) {
+    let mut modified = modified_at(&tls);
+    let mut interval = tokio::time::interval(Duration::from_millis(tls.reload_interval));
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        let Some(config) = config.upgrade() else {
+            break;
+        };
+        let current = modified_at(&tls);
+        if current == modified {
+            continue;
+        }
+        match server_config(&tls) {
+            Ok(reloaded) => {
+                *config.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(reloaded);
+                modified = current;
+                tracing::info!(cert = tls.cert, "tls certificates reloaded");
+            }
+            // Keep serving the previous certificates, files may be half written.
+            Err(e) => tracing::error!(error = %e, "failed to reload tls certificates"),
+        }
+    }
+}
+
+fn modified_at(tls: &ServerTls) -> Vec<Option<SystemTime>> {
+    [Some(&tls.cert), Some(&tls.key), tls.client_ca.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+fn server_config(tls: &ServerTls) -> Result<ServerConfig> {
+    let provider = Arc::new(ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?;
+
+    let builder = match &tls.client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(client_ca)? {
+                roots.add(cert).map_err(tls_error)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(tls_error)?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(load_certs(&tls.cert)?, load_key(&tls.key)?)
+        .map_err(tls_error)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path).map_err(|e| path_error(path, e))?);
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| path_error(path, e))?;
+    if certs.is_empty() {
+        return Err(path_error(path, "no certificate found"));
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path).map_err(|e| path_error(path, e))?);
+    rustls_pemfile::private_key(&mut reader)
+        .map_err(|e| path_error(path, e))?
+        .ok_or_else(|| path_error(path, "no private key found"))
+}
+
+fn path_error(path: &str, e: impl std::fmt::Display) -> Error {
+    Error::Message(format!("tls {path}: {e}"))
+}
+
+fn tls_error(e: impl std::fmt::Display) -> Error {
+    Error::Message(format!("tls: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{connection::ConnectionOptions, serve_listener, tests::http_get};
+    use axum::{routing::get, Router};
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use std::path::PathBuf;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_rustls::{
+        rustls::{pki_types::ServerName, ClientConfig},
+        TlsConnector,
+    };
+
+    struct Ca {
+        cert: rcgen::Certificate,
+        key: KeyPair,
+    }
+
+    impl Ca {
+        fn new() -> Self {
+            let mut params = CertificateParams::new(vec![]).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let key = KeyPair::generate().unwrap();
+            let cert = params.self_signed(&key).unwrap();
+            Self { cert, key }
+        }
+
+        /// Issue a `localhost` certificate, returns the certificate and key PEM.
+        fn issue(&self) -> (String, String) {
+            let params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+            let key = KeyPair::generate().unwrap();
+            let cert = params.signed_by(&key, &self.cert, &self.key).unwrap();
+            (cert.pem(), key.serialize_pem())
+        }
+    }
+
+    fn write_tls(dir: &PathBuf, (cert, key): &(String, String)) -> ServerTls {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("server.crt"), cert).unwrap();
+        std::fs::write(dir.join("server.key"), key).unwrap();
+        ServerTls {
+            cert: dir.join("server.crt").to_string_lossy().to_string(),
+            key: dir.join("server.key").to_string_lossy().to_string(),
+            client_ca: None,
+            reload_interval: 0,
+            redirect_port: None,
+        }
+    }
+
+    fn connector(ca: &Ca) -> TlsConnector {
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.cert.der().clone()).unwrap();
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        TlsConnector::from(Arc::new(config))
+    }
+
+    async fn start(
+        acceptor: TlsAcceptor,
+    ) -> (std::net::SocketAddr, tokio::sync::oneshot::Sender<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/", get(|| async { "secure" }));
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        tokio::spawn(serve_listener(
+            listener,
+            app,
+            Some(acceptor),
+            ConnectionOptions::default(),
+            async {
+                let _ = rx.await;
+            },
+        ));
+        (addr, tx)
+    }
+
+    /// Connect over TLS and return the certificate presented by the server.
+    async fn peer_cert(connector: &TlsConnector, addr: std::net::SocketAddr) -> Vec<u8> {
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let stream = connector
+            .connect(ServerName::try_from("localhost").unwrap(), tcp)
+            .await
+            .unwrap();
+        let cert = stream.get_ref().1.peer_certificates().unwrap()[0].to_vec();
+        let response = http_get(stream, "/").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("secure"));
+        cert
+    }
+
+    fn cert_der(pem: &str) -> Vec<u8> {
+        rustls_pemfile::certs(&mut pem.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_tls_serve_and_reload() {
+        let dir = std::env::temp_dir().join(format!("ymir-tls-{}", ulid::Ulid::new()));
+        let ca = Ca::new();
+        let first = ca.issue();
+        let mut tls = write_tls(&dir, &first);
+        tls.reload_interval = 20;
+
+        let (addr, _stop) = start(TlsAcceptor::from_config(&tls).unwrap()).await;
+        let connector = connector(&ca);
+        assert_eq!(peer_cert(&connector, addr).await, cert_der(&first.0));
+
+        let second = ca.issue();
+        // Make sure the modification time changes on coarse file systems.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        write_tls(&dir, &second);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(peer_cert(&connector, addr).await, cert_der(&second.0));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mtls_rejects_anonymous_client() {
+        let dir = std::env::temp_dir().join(format!("ymir-mtls-{}", ulid::Ulid::new()));
+        let ca = Ca::new();
+        let mut tls = write_tls(&dir, &ca.issue());
+        std::fs::write(dir.join("ca.crt"), ca.cert.pem()).unwrap();
+        tls.client_ca = Some(dir.join("ca.crt").to_string_lossy().to_string());
+
+        let (addr, _stop) = start(TlsAcceptor::from_config(&tls).unwrap()).await;
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let response = match connector(&ca)
+            .connect(ServerName::try_from("localhost").unwrap(), tcp)
+            .await
+        {
+            Ok(stream) => http_get(stream, "/").await,
+            Err(_) => String::new(),
+        };
+        assert!(!response.contains("200 OK"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_certificate() {
+        let tls = ServerTls {
+            cert: "missing.crt".to_string(),
+            key: "missing.key".to_string(),
+            client_ca: None,
+            reload_interval: 0,
+            redirect_port: None,
+        };
+        let err = server_config(&tls).err().unwrap();
+        assert!(err.to_string().starts_with("tls missing.crt:"));
+    }
+}
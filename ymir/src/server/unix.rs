@@ -0,0 +1,47 @@
+use std::{
+    fs::{self, Permissions},
+    io,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::Path,
+    sync::Arc,
+};
+
+use tokio::net::{unix::SocketAddr, UnixListener, UnixStream};
+
+use super::Listener;
+
+/// Peer of a connection accepted on a Unix domain socket, extracted with
+/// `ConnectInfo<UnixConnectInfo>`.
+#[derive(Debug, Clone)]
+pub struct UnixConnectInfo {
+    pub peer_addr: Arc<SocketAddr>,
+}
+
+impl Listener for UnixListener {
+    type Io = UnixStream;
+    type Addr = UnixConnectInfo;
+
+    async fn accept(&mut self) -> io::Result<(Self::Io, Self::Addr)> {
+        let (stream, addr) = UnixListener::accept(self).await?;
+        Ok((
+            stream,
+            UnixConnectInfo {
+                peer_addr: Arc::new(addr),
+            },
+        ))
+    }
+}
+
+/// Bind a Unix domain socket at `path`, replacing a stale socket left by a
+/// previous run, and apply `mode` to the socket file.
+pub fn bind(path: &str, mode: Option<u32>) -> io::Result<UnixListener> {
+    let path = Path::new(path);
+    if fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    if let Some(mode) = mode {
+        fs::set_permissions(path, Permissions::from_mode(mode))?;
+    }
+    Ok(listener)
+}
@@ -0,0 +1,300 @@
+//! Typed settings of the application.
+//!
+//! The `settings` section of the configuration is bound once at startup,
+//! before any adapter runs, to the type returned by
+//! [`crate::hook::LifeCycle::settings`] and checked with
+//! [`AppSettings::validate`]. Handlers extract it with [`Settings`] without
+//! parsing it again.
+//!
+//! Example
+//! ```rust,ignore
+//! #[derive(Debug, Deserialize)]
+//! struct Jwt {
+//!     secret: String,
+//!     expiration: u64,
+//! }
+//!
+//! impl AppSettings for Jwt {
+//!     fn validate(&self) -> Vec<ConfigIssue> {
+//!         if self.expiration == 0 {
+//!             return vec![ConfigIssue::new("expiration", "must be greater than zero")];
+//!         }
+//!         vec![]
+//!     }
+//! }
+//!
+//! // In the `LifeCycle` implementation:
+//! fn settings() -> Option<SettingsBinding> {
+//!     Some(SettingsBinding::of::<Jwt>())
+//! }
+//!
+//! async fn token(Settings(jwt): Settings<Jwt>) -> String {
+//!     format!("expires in {}", jwt.expiration)
+//! }
+//! ```
+use std::{
+    any::{type_name, Any},
+    fmt::Debug,
+    ops::Deref,
+    sync::Arc,
+};
+
+use axum::extract::FromRequestParts;
+use http::request::Parts;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    config::{Config, ConfigErrors, ConfigIssue},
+    context::Context,
+    errors::Error,
+};
+
+/// Type of the `settings` section of the configuration.
+pub trait AppSettings: DeserializeOwned + Send + Sync + 'static {
+    /// Problems of the values, their path is relative to `settings`.
+    fn validate(&self) -> Vec<ConfigIssue> {
+        Vec::new()
+    }
+}
+
+/// Settings bound at startup, set in the context and the request
+/// extensions.
+#[derive(Clone)]
+pub(crate) struct BoundSettings(Arc<dyn Any + Send + Sync>);
+
+/// Settings type registered by [`crate::hook::LifeCycle::settings`].
+#[derive(Debug, Clone, Copy)]
+pub struct SettingsBinding {
+    type_name: &'static str,
+    bind: fn(&Config) -> Result<BoundSettings, ConfigErrors>,
+}
+
+impl SettingsBinding {
+    #[must_use]
+    pub fn of<T: AppSettings>() -> Self {
+        Self {
+            type_name: type_name::<T>(),
+            bind: |config| bind::<T>(config).map(|settings| BoundSettings(settings)),
+        }
+    }
+
+    /// Name of the settings type
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Deserialize and validate the `settings` of `config`, every problem is
+    /// returned with its path.
+    pub fn check(&self, config: &Config) -> Result<(), ConfigErrors> {
+        (self.bind)(config).map(drop)
+    }
+
+    pub(crate) fn bind(&self, config: &Config) -> Result<BoundSettings, ConfigErrors> {
+        (self.bind)(config)
+    }
+}
+
+fn bind<T: AppSettings>(config: &Config) -> Result<Arc<T>, ConfigErrors> {
+    let empty = serde_json::Value::Object(serde_json::Map::new());
+    let section = config.settings.as_ref().unwrap_or(&empty);
+    let settings: T = serde_path_to_error::deserialize(section).map_err(|e| {
+        let path = e.path().to_string();
+        ConfigErrors(vec![ConfigIssue::new(
+            settings_path(if path == "." { "" } else { &path }),
+            e.into_inner().to_string(),
+        )])
+    })?;
+
+    let issues: Vec<_> = settings
+        .validate()
+        .into_iter()
+        .map(|issue| ConfigIssue::new(settings_path(&issue.path), issue.message))
+        .collect();
+    if !issues.is_empty() {
+        return Err(ConfigErrors(issues));
+    }
+    Ok(Arc::new(settings))
+}
+
+fn settings_path(path: &str) -> String {
+    if path.is_empty() {
+        "settings".to_string()
+    } else {
+        format!("settings.{path}")
+    }
+}
+
+/// Extractor of the settings registered by
+/// [`crate::hook::LifeCycle::settings`], shared without copy between the
+/// requests.
+pub struct Settings<T>(pub Arc<T>);
+
+impl<T> Clone for Settings<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Debug> Debug for Settings<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Settings").field(&self.0).finish()
+    }
+}
+
+impl<T> Deref for Settings<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Send + Sync + 'static> Settings<T> {
+    /// Settings of the context, `None` when `T` is not the registered type.
+    pub fn from_context(ctx: &Context) -> Option<Self> {
+        ctx.get::<BoundSettings>().and_then(Self::downcast)
+    }
+
+    fn downcast(bound: &BoundSettings) -> Option<Self> {
+        bound.0.clone().downcast::<T>().ok().map(Self)
+    }
+}
+
+impl<T, S> FromRequestParts<S> for Settings<T>
+where
+    T: Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<BoundSettings>()
+            .and_then(Self::downcast)
+            .ok_or_else(|| {
+                Error::InternalServerError(format!(
+                    "settings of type `{}` are not registered, see `LifeCycle::settings`",
+                    type_name::<T>()
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hook::LifeCycle, testing::TestServer};
+    use axum::{routing::get, Router};
+    use http::StatusCode;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Jwt {
+        secret: String,
+        expiration: u64,
+    }
+
+    impl AppSettings for Jwt {
+        fn validate(&self) -> Vec<ConfigIssue> {
+            let mut issues = vec![];
+            if self.secret.len() < 8 {
+                issues.push(ConfigIssue::new(
+                    "secret",
+                    "must hold at least 8 characters",
+                ));
+            }
+            if self.expiration == 0 {
+                issues.push(ConfigIssue::new("expiration", "must be greater than zero"));
+            }
+            issues
+        }
+    }
+
+    struct MockLifeCycle;
+
+    impl LifeCycle for MockLifeCycle {
+        fn app_name() -> &'static str {
+            "settings-app"
+        }
+
+        fn settings() -> Option<SettingsBinding> {
+            Some(SettingsBinding::of::<Jwt>())
+        }
+
+        fn routes(ctx: Context) -> Router {
+            Router::new()
+                .route(
+                    "/expiration",
+                    get(|Settings(jwt): Settings<Jwt>| async move { jwt.expiration.to_string() }),
+                )
+                .route(
+                    "/unregistered",
+                    get(|_: Settings<String>| async { "unreachable" }),
+                )
+                .with_state(ctx)
+        }
+    }
+
+    fn config(settings: serde_json::Value) -> Config {
+        crate::testing::config_with(|cfg| cfg.settings = Some(settings))
+    }
+
+    #[tokio::test]
+    async fn test_settings_extractor() {
+        let server = TestServer::builder()
+            .config(|cfg| {
+                cfg.settings =
+                    Some(serde_json::json!({ "secret": "0123456789", "expiration": 15 }));
+            })
+            .start::<MockLifeCycle>()
+            .await
+            .unwrap();
+        let jwt = Settings::<Jwt>::from_context(server.ctx()).unwrap();
+        assert_eq!(jwt.secret, "0123456789");
+        assert!(Settings::<String>::from_context(server.ctx()).is_none());
+
+        let res = server.get("/expiration").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text(), "15");
+        let res = server.get("/unregistered").await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        server.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_settings_binding_errors() {
+        let binding = SettingsBinding::of::<Jwt>();
+        assert!(binding.type_name().ends_with("Jwt"));
+        assert!(binding
+            .check(&config(
+                serde_json::json!({ "secret": "0123456789", "expiration": 1 })
+            ))
+            .is_ok());
+
+        let errors = binding
+            .check(&config(
+                serde_json::json!({ "secret": "short", "expiration": 0 }),
+            ))
+            .unwrap_err();
+        assert_eq!(
+            errors.to_string(),
+            "invalid configuration, 2 problem(s):\n  \
+             settings.secret: must hold at least 8 characters\n  \
+             settings.expiration: must be greater than zero"
+        );
+
+        let errors = binding
+            .check(&config(
+                serde_json::json!({ "secret": "0123456789", "expiration": "soon" }),
+            ))
+            .unwrap_err();
+        assert_eq!(errors.0[0].path, "settings.expiration");
+
+        let mut missing = config(serde_json::json!({}));
+        missing.settings = None;
+        let errors = binding.check(&missing).unwrap_err();
+        assert_eq!(errors.0[0].path, "settings");
+        assert!(errors.0[0].message.contains("missing field"));
+    }
+}
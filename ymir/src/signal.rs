@@ -1,4 +1,8 @@
-use tokio::signal;
+use std::{future::Future, sync::Arc};
+
+use tokio::{signal, sync::watch};
+
+use crate::context::Context;
 
 pub async fn shutdown_signal() {
     let ctrl_c = async {
@@ -27,3 +31,80 @@ pub async fn shutdown_signal() {
         },
     }
 }
+
+/// Phases of the graceful shutdown, notified to the adapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownPhase {
+    /// The shutdown signal was received and `/readyz` reports not ready,
+    /// requests are still served during the `pre_stop_delay`.
+    PreStop,
+    /// The listener is closed and in-flight requests are draining.
+    Draining,
+    /// In-flight requests are finished, `forced` is `true` when the
+    /// `drain_timeout` was reached and remaining connections were dropped.
+    Drained { forced: bool },
+}
+
+/// Shared trigger telling `LifeCycle::rest` to stop accepting connections.
+#[derive(Debug, Clone)]
+pub struct Drain(Arc<watch::Sender<bool>>);
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+}
+
+impl Drain {
+    /// Start draining the connections.
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait until the drain is triggered.
+    pub async fn wait(&self) {
+        let mut rx = self.0.subscribe();
+        let _ = rx.wait_for(|draining| *draining).await;
+    }
+}
+
+/// Future given to `axum::serve(..).with_graceful_shutdown`.
+///
+/// Resolves when the [`Drain`] of the context is triggered, or falls back to
+/// [`shutdown_signal`] when the context has none, e.g. when `LifeCycle::rest`
+/// is called outside of `startup::run`.
+pub fn drain_signal(ctx: &Context) -> impl Future<Output = ()> + Send + 'static {
+    let drain = ctx.get::<Drain>().cloned();
+    async move {
+        match drain {
+            Some(drain) => drain.wait().await,
+            None => shutdown_signal().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_drain_signal() {
+        let mut ctx = Context::new();
+        let drain = Drain::default();
+        ctx.set(drain.clone());
+
+        let signal = tokio::spawn(drain_signal(&ctx));
+        assert!(!drain.is_triggered());
+        drain.trigger();
+        assert!(drain.is_triggered());
+        tokio::time::timeout(Duration::from_secs(1), signal)
+            .await
+            .expect("drain signal did not resolve")
+            .unwrap();
+    }
+}
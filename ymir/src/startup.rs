@@ -1,17 +1,29 @@
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use axum::Router;
+use axum::{Extension, Router};
 use tower_http::services::{ServeDir, ServeFile};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    adapter::AdapterManager,
-    config::{load_configuration, Environment},
+    adapter::{AdapterManager, AdapterOpenApi, AdapterStatuses},
+    config::{Config, ConfigLoader, Environment, Logger},
     context::Context,
     errors::{self, Error},
+    event::EventBus,
+    health::{self, ErrorDetails, Readiness},
     hook::LifeCycle,
     interception::interception_fn,
     logo::print_logo,
+    reload::{self, ConfigWatch},
+    scheduler,
+    settings::BoundSettings,
+    signal::{Drain, ShutdownPhase},
+    timeline::StartupTimeline,
+    worker::Supervisor,
     Result,
 };
 
@@ -23,34 +35,96 @@ const MODULE_WHITELIST: &[&str] = &[
     "sqlx",
 ];
 
-/// Create context application.
-pub async fn create_context() -> Result<Context> {
+/// Environment selected by `APP_ENVIRONMENT`, `development` when unset.
+fn app_environment() -> Result<Environment> {
     dotenvy::dotenv_override().ok();
-    // Detect the running environment.
-    // Default to `development` if unspecified.
-    let environment: Environment = std::env::var("APP_ENVIRONMENT")
+    std::env::var("APP_ENVIRONMENT")
         .unwrap_or_else(|_| "development".into())
         .try_into()
-        .expect("Failed to parse APP_ENVIRONMENT.");
+        .map_err(Error::Message)
+}
+
+/// Create context application.
+pub async fn create_context() -> Result<Context> {
+    create_context_for(app_environment()?).await
+}
 
-    let configs = load_configuration(&environment).expect("Failed to read configurations.");
+/// Create context application for the given environment.
+pub async fn create_context_for(environment: Environment) -> Result<Context> {
+    create_context_with(ConfigLoader::default(), environment).await
+}
+
+/// Create context application for the given environment, loading the
+/// configuration with `loader`. The loader is kept in the context for the
+/// live reload.
+///
+/// The configuration is validated, every problem is returned in a
+/// [`crate::config::ConfigErrors`] before any adapter runs.
+pub async fn create_context_with(
+    loader: ConfigLoader,
+    environment: Environment,
+) -> Result<Context> {
+    let timeline = StartupTimeline::default();
+    let started = Instant::now();
+    let configs = timeline.step("config load", || {
+        loader.load(&environment).map_err(Error::wrap)
+    })?;
+    context(loader, environment, configs, timeline, started)
+}
+
+/// Create context application like [`create_context_with`] from an already
+/// loaded configuration, `overrides` is applied before it is validated.
+pub fn create_context_from(
+    loader: ConfigLoader,
+    environment: Environment,
+    mut configs: Config,
+    overrides: impl FnOnce(&mut Config),
+) -> Result<Context> {
+    let timeline = StartupTimeline::default();
+    let started = Instant::now();
+    overrides(&mut configs);
+    context(loader, environment, configs, timeline, started)
+}
 
-    Ok(Context {
-        environment: Some(environment.clone()),
+fn context(
+    loader: ConfigLoader,
+    environment: Environment,
+    configs: Config,
+    timeline: StartupTimeline,
+    started: Instant,
+) -> Result<Context> {
+    timeline.step("config validate", || configs.validate())?;
+    let mut ctx = Context {
+        environment: Some(environment),
         configs: Some(configs),
         extend: Some(Box::default()),
-    })
+    };
+    timeline.record("context", started, started.elapsed());
+    ctx.set(timeline);
+    ctx.set(loader);
+    Ok(ctx)
 }
 
 /// Create axum router.
 pub async fn router_init<LC: LifeCycle>(ctx: &Context) -> Result<Router> {
     let config = ctx.configs.clone().expect("load configuration failed.");
+    let readiness = ctx.get::<Readiness>().cloned().unwrap_or_default();
+    let statuses = ctx.get::<AdapterStatuses>().cloned().unwrap_or_default();
     // build our application with a route
     let mut app = axum::Router::new()
         .merge(LC::routes(ctx.clone()))
         // .merge(health::register_handler(ctx.clone()))
-        .layer(tower_http::trace::TraceLayer::new_for_http());
-    app = interception_fn(ctx.clone(), app.clone());
+        .layer(Extension(readiness))
+        .layer(Extension(statuses))
+        .layer(Extension(ErrorDetails(config.profile.error_details())));
+    if let Some(doc) = ctx.get::<AdapterOpenApi>() {
+        app = app.layer(Extension(doc.clone()));
+    }
+    if let Some(settings) = ctx.get::<BoundSettings>() {
+        app = app.layer(Extension(settings.clone()));
+    }
+    app = app.layer(tower_http::trace::TraceLayer::new_for_http());
+    app = interception_fn(ctx.clone(), app.clone())?;
 
     // Static Assets
     if let Some(assets) = config
@@ -85,29 +159,234 @@ pub async fn router_init<LC: LifeCycle>(ctx: &Context) -> Result<Router> {
     Ok(app)
 }
 
+/// Create the admin router from the health endpoints, [`health::routes`], and
+/// [`LifeCycle::admin_routes`]. Without admin routes the health endpoints are
+/// only served when an admin listener is configured.
+pub fn admin_init<LC: LifeCycle>(ctx: &Context) -> Option<Router> {
+    let readiness = ctx.get::<Readiness>().cloned().unwrap_or_default();
+    let statuses = ctx.get::<AdapterStatuses>().cloned().unwrap_or_default();
+    let doc = ctx.get::<AdapterOpenApi>().cloned();
+    let settings = ctx.get::<BoundSettings>().cloned();
+    let admin = match LC::admin_routes(ctx.clone()) {
+        Some(admin) => Some(health::routes().fallback_service(admin)),
+        None if ctx
+            .configs
+            .as_ref()
+            .is_some_and(|configs| configs.server.has_admin_listener()) =>
+        {
+            Some(health::routes())
+        }
+        None => None,
+    };
+    admin.map(|mut admin| {
+        if let Some(doc) = doc {
+            admin = admin.layer(Extension(doc));
+        }
+        if let Some(settings) = settings {
+            admin = admin.layer(Extension(settings));
+        }
+        admin
+            .layer(Extension(readiness))
+            .layer(Extension(statuses))
+            .layer(Extension(ErrorDetails(true)))
+            .layer(tower_http::trace::TraceLayer::new_for_http())
+    })
+}
+
+/// Application assembled by [`boot`], ready to be served.
+pub struct BootedApp {
+    /// Context returned by the adapters `before_run` hook.
+    pub ctx: Context,
+    /// Router configured by the adapters `after_route` hook.
+    pub router: Router,
+    /// Router served on the `admin` listeners.
+    pub admin: Option<Router>,
+    pub adapter_manager: AdapterManager,
+}
+
+/// Register the adapters of the application and build its router.
+pub async fn boot<LC: LifeCycle>(mut ctx: Context) -> Result<BootedApp> {
+    let timeline = ctx.get::<StartupTimeline>().cloned().unwrap_or_else(|| {
+        let timeline = StartupTimeline::default();
+        ctx.set(timeline.clone());
+        timeline
+    });
+    if let (Some(binding), Some(configs)) = (LC::settings(), ctx.configs.as_ref()) {
+        let settings = timeline.step("settings", || binding.bind(configs))?;
+        ctx.set(settings);
+    }
+    ctx.set(Readiness::default());
+    ctx.set(Drain::default());
+    if let Some(configs) = ctx.configs.clone() {
+        ctx.set(EventBus::new(configs.events.clone()));
+        ctx.set(ConfigWatch::new(configs));
+    }
+    let mut adapter_manager = AdapterManager::new(ctx);
+    let adapters = timeline
+        .step_async("adapters register", LC::adapters())
+        .await?;
+    for adapter in adapters {
+        adapter_manager.register(adapter);
+    }
+    adapter_manager.init_all().await?;
+    let mut ctx = adapter_manager.before_run().await?;
+    if let Some(doc) = adapter_manager.openapi() {
+        ctx.set(AdapterOpenApi(Arc::new(doc)));
+    }
+    if let Some(events) = ctx.get::<EventBus>() {
+        timeline
+            .step_async("event subscribers", async {
+                let mut subscribers = LC::subscribers(&ctx).await?;
+                subscribers.extend(adapter_manager.subscribers());
+                for subscriber in subscribers {
+                    events.subscribe(&ctx, subscriber)?;
+                }
+                Ok::<_, Error>(())
+            })
+            .await?;
+    }
+    let router = timeline
+        .step_async("router", router_init::<LC>(&ctx))
+        .await?;
+    let router = adapter_manager.configure_routes(router).await?;
+    let admin = timeline.step("admin router", || admin_init::<LC>(&ctx));
+    if !timeline
+        .step_async("health check", adapter_manager.check_health())
+        .await
+    {
+        tracing::warn!("a required adapter is unhealthy, /readyz reports not ready");
+    }
+
+    Ok(BootedApp {
+        ctx,
+        router,
+        admin,
+        adapter_manager,
+    })
+}
+
+/// Start the workers and the scheduled jobs of the application and its
+/// adapters.
+pub async fn start_workers<LC: LifeCycle>(
+    ctx: &Context,
+    adapter_manager: &AdapterManager,
+) -> Result<Supervisor> {
+    let timeline = ctx.get::<StartupTimeline>().cloned().unwrap_or_default();
+    timeline
+        .step_async("workers", async {
+            let mut workers = LC::workers(ctx).await?;
+            let mut jobs = LC::jobs(ctx).await?;
+            jobs.extend(adapter_manager.jobs());
+            workers.extend(scheduler::workers(ctx, jobs)?);
+            Ok(Supervisor::start(
+                ctx.clone(),
+                workers,
+                adapter_manager.shutdown_signal(),
+            ))
+        })
+        .await
+}
+
+/// Serve the application until [`LifeCycle::shutdown_signal`] resolves, then
+/// drain it following the `server.shutdown` configuration:
+///
+/// 1. `/readyz` reports not ready while requests are served for
+///    `pre_stop_delay` milliseconds.
+/// 2. The listener is closed and in-flight requests are drained.
+/// 3. After `drain_timeout` milliseconds remaining connections are dropped.
+///
+/// Adapters are notified at each phase through [`AdapterManager::notify_shutdown`].
+pub async fn serve<LC: LifeCycle>(
+    ctx: &Context,
+    app: Router,
+    admin: Option<Router>,
+    adapter_manager: &AdapterManager,
+) -> Result<()> {
+    let config = ctx.configs.clone().expect("load configuration failed.");
+    let drain = ctx.get::<Drain>().cloned().unwrap_or_default();
+
+    let mut server = Box::pin(LC::rest(ctx, app, admin));
+    tokio::select! {
+        result = &mut server => return result,
+        () = LC::shutdown_signal() => {}
+        () = reload::watch_config(ctx, adapter_manager) => {}
+        () = adapter_manager.watch_health() => {}
+    }
+
+    if let Some(readiness) = ctx.get::<Readiness>() {
+        readiness.set_ready(false);
+    }
+    adapter_manager
+        .notify_shutdown(ShutdownPhase::PreStop)
+        .await?;
+    tokio::select! {
+        result = &mut server => return result,
+        () = tokio::time::sleep(Duration::from_millis(config.server.shutdown.pre_stop_delay)) => {}
+    }
+
+    drain.trigger();
+    adapter_manager
+        .notify_shutdown(ShutdownPhase::Draining)
+        .await?;
+    let drained = tokio::time::timeout(
+        Duration::from_millis(config.server.shutdown.drain_timeout),
+        &mut server,
+    )
+    .await;
+    let forced = drained.is_err();
+    if forced {
+        tracing::warn!("drain timeout reached, closing remaining connections");
+    }
+    // Abort the connections still open before the adapters stop.
+    drop(server);
+    adapter_manager
+        .notify_shutdown(ShutdownPhase::Drained { forced })
+        .await?;
+    drained.unwrap_or(Ok(()))
+}
+
+/// Stop the adapters and wait for the supervised workers to return.
+pub async fn shutdown(adapter_manager: &AdapterManager, supervisor: Supervisor) -> Result<()> {
+    let (stopped, ()) = tokio::join!(adapter_manager.stop_all(), supervisor.join());
+    stopped
+}
+
 /// Run the impl app struct to the application.
 pub async fn run<LC: LifeCycle>() -> Result<()> {
-    let ctx = create_context().await.expect("create context is failed.");
+    let ctx = create_context_with(LC::config_loader(), app_environment()?).await?;
+    start::<LC>(ctx).await
+}
+
+/// Tracing filter of the configured logger level, `error` when the logger
+/// is disabled.
+fn logger_filter<LC: LifeCycle>(
+    logger: &Logger,
+) -> std::result::Result<tracing_subscriber::EnvFilter, tracing_subscriber::filter::ParseError> {
+    let level = if logger.enable {
+        logger.level.as_str()
+    } else {
+        "error"
+    };
+    tracing_subscriber::EnvFilter::try_new(
+        MODULE_WHITELIST
+            .iter()
+            .map(|m| format!("{m}={level}"))
+            .chain(std::iter::once(format!("{}={}", LC::app_name(), level)))
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+/// Run the impl app struct with an already created context.
+pub async fn start<LC: LifeCycle>(ctx: Context) -> Result<()> {
     let conf = ctx.configs.clone().expect("load configuration failed.");
-    let logger = conf.logger.clone();
-    let level = logger
-        .enable
-        .then(|| logger.level)
-        .or_else(|| Some("error".to_string()))
-        .unwrap();
-
-    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .or_else(|_| {
-            tracing_subscriber::EnvFilter::try_new(
-                MODULE_WHITELIST
-                    .iter()
-                    .map(|m| format!("{m}={level}"))
-                    .chain(std::iter::once(format!("{}={}", LC::app_name(), level)))
-                    .collect::<Vec<_>>()
-                    .join(","),
-            )
-        })
-        .expect("tracing filter failed");
+    let from_env = tracing_subscriber::EnvFilter::try_from_default_env().ok();
+    let reload_level = from_env.is_none() && conf.reload.enable;
+    let env_filter = match from_env {
+        Some(filter) => filter,
+        None => logger_filter::<LC>(&conf.logger).expect("tracing filter failed"),
+    };
+    let (env_filter, filter_handle) = tracing_subscriber::reload::Layer::new(env_filter);
     tracing_subscriber::registry()
         .with(env_filter)
         .with(tracing_subscriber::fmt::layer())
@@ -116,71 +395,48 @@ pub async fn run<LC: LifeCycle>() -> Result<()> {
     print_logo(ctx.environment.clone().unwrap(), conf.clone());
     println!("version: {}", LC::version());
 
-    let mut adapter_manager = AdapterManager::new(ctx);
-    let adapters = LC::adapters().await?;
-    for adapter in adapters {
-        adapter_manager.register(adapter);
-    }
-    adapter_manager.init_all().await?;
-    let ctx = adapter_manager.before_run().await?;
-    let router = router_init::<LC>(&ctx).await?;
-    let app = adapter_manager.configure_routes(router).await?;
-
-    #[cfg(test)]
-    {
-        use std::{
-            sync::{Arc, Mutex},
-            time::Duration,
-        };
+    let BootedApp {
+        ctx,
+        router: app,
+        admin,
+        adapter_manager,
+    } = boot::<LC>(ctx).await?;
+    let supervisor = start_workers::<LC>(&ctx, &adapter_manager).await?;
 
-        // Use a mocked signal for testing
-        let mock_shutdown_signal =
-            Arc::new(Mutex::new(Some(tokio::sync::oneshot::channel::<()>())));
-        let mock_signal_receiver = mock_shutdown_signal.lock().unwrap().take().unwrap().1;
-        let shutdown_signal = async {
-            tokio::time::sleep(Duration::from_secs(3)).await;
-            let _ = mock_signal_receiver.await;
-        };
-        tokio::select! {
-            result = LC::rest(&ctx, app) => {
-                if let Err(err) = result {
-                    return Err(Error::Message(err.to_string()));
+    // `RUST_LOG` takes precedence over the configured level.
+    if let Some(watch) = ctx.get::<ConfigWatch>().filter(|_| reload_level) {
+        let mut rx = watch.subscribe();
+        tokio::spawn(async move {
+            while rx.changed().await.is_ok() {
+                let filter = logger_filter::<LC>(&rx.borrow_and_update().logger);
+                let reloaded = filter
+                    .map_err(|e| e.to_string())
+                    .and_then(|filter| filter_handle.reload(filter).map_err(|e| e.to_string()));
+                if let Err(e) = reloaded {
+                    tracing::error!(error = %e, "failed to reload the logger level");
                 }
             }
-            _ = shutdown_signal => {
-                println!("Received shutdown signal");
-            }
-        }
-    }
-    #[cfg(not(test))]
-    {
-        if let Err(err) = LC::rest(&ctx, app).await {
-            return Err(Error::Message(err.to_string()));
-        }
+        });
     }
 
-    let mut rx = adapter_manager.shutdown_signal();
-    tokio::spawn(async move {
-        adapter_manager.stop_all().await.unwrap();
-    });
-
-    match rx.recv().await {
-        Ok(()) => Ok(()),
-        Err(e) => Err(Error::Message(format!("{}", e))),
+    if let Err(err) = serve::<LC>(&ctx, app, admin, &adapter_manager).await {
+        return Err(Error::Message(err.to_string()));
     }
+
+    shutdown(&adapter_manager, supervisor).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::adapter::Adapter;
+    use crate::{
+        adapter::Adapter,
+        scheduler::{Job, Schedule},
+        worker::Worker,
+    };
     use async_trait::async_trait;
     use axum::{body::Body, extract::Request};
-    use std::{
-        sync::{Arc, Mutex},
-        time::Duration,
-    };
-    use tokio::sync::oneshot;
+    use std::time::Duration;
     use tower::ServiceExt; // for `oneshot` method
 
     #[tokio::test]
@@ -215,22 +471,82 @@ mod tests {
 
     #[tokio::test]
     async fn test_run() {
-        // Create a mock shutdown signal for testing
-        let mock_shutdown_signal = Arc::new(Mutex::new(Some(oneshot::channel::<()>())));
-        let run_handle = tokio::spawn(async move { run::<MockLifeCycle>().await });
-        // Simulate sending a shutdown signal
-        {
-            let shutdown_signal = mock_shutdown_signal.lock().unwrap().take().unwrap().0;
-            let _ = shutdown_signal.send(());
-        }
-        let result = tokio::time::timeout(Duration::from_secs(5), run_handle).await;
+        let result = tokio::time::timeout(Duration::from_secs(5), run::<MockLifeCycle>()).await;
         match result {
-            Ok(Ok(_)) => assert!(true),
+            Ok(Ok(())) => {}
             Ok(Err(e)) => panic!("run function failed: {}", e),
             Err(_) => panic!("run function timed out"),
         }
     }
 
+    #[tokio::test]
+    async fn test_readiness() {
+        let ctx = create_context().await.unwrap();
+        let app = boot::<MockLifeCycle>(ctx).await.expect("failed to boot");
+        let readyz = || {
+            app.router.clone().oneshot(
+                Request::builder()
+                    .uri("/readyz")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        assert_eq!(readyz().await.unwrap().status(), 200);
+
+        app.ctx.get::<Readiness>().unwrap().set_ready(false);
+        assert_eq!(readyz().await.unwrap().status(), 503);
+    }
+
+    #[tokio::test]
+    async fn test_boot_and_shutdown() {
+        let ctx = create_context().await.unwrap();
+        let app = boot::<MockLifeCycle>(ctx).await.expect("failed to boot");
+        let response = app
+            .router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("Failed to execute request");
+        assert_eq!(response.status(), 200);
+
+        let supervisor = start_workers::<MockLifeCycle>(&app.ctx, &app.adapter_manager)
+            .await
+            .unwrap();
+        assert_eq!(supervisor.len(), 2);
+
+        let report = app.ctx.get::<StartupTimeline>().unwrap().finish().unwrap();
+        let steps: Vec<_> = report.steps.iter().map(|s| s.name.as_str()).collect();
+        for step in [
+            "config load",
+            "context",
+            "adapters register",
+            "adapters configure",
+            "interception request id",
+            "router",
+            "health check",
+            "workers",
+        ] {
+            assert!(steps.contains(&step), "missing step `{step}` in {steps:?}");
+        }
+        assert!(report.slowest.len() <= 5);
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            shutdown(&app.adapter_manager, supervisor),
+        )
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => panic!("shutdown failed: {}", e),
+            Err(_) => panic!("shutdown timed out"),
+        }
+    }
+
     struct MockLifeCycle;
 
     #[async_trait]
@@ -246,11 +562,28 @@ mod tests {
         fn routes(ctx: Context) -> Router {
             Router::new()
                 .route("/health", axum::routing::get(|| async { "OK" }))
+                .route("/readyz", axum::routing::get(crate::health::readyz))
                 .with_state(ctx)
         }
 
+        async fn shutdown_signal() {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+
         async fn adapters() -> Result<Vec<Box<dyn Adapter>>> {
             Ok(vec![])
         }
+
+        async fn workers(_ctx: &Context) -> Result<Vec<Worker>> {
+            Ok(vec![Worker::new("idle", |_ctx, mut shutdown| async move {
+                shutdown.recv().await;
+                Ok(())
+            })])
+        }
+
+        async fn jobs(_ctx: &Context) -> Result<Vec<Job>> {
+            Ok(vec![Job::new("noop", |_ctx| async { Ok(()) })
+                .schedule(Schedule::Interval(Duration::from_secs(60)))])
+        }
     }
 }
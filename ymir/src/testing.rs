@@ -0,0 +1,485 @@
+use std::net::SocketAddr;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::connect_info::MockConnectInfo,
+    Router,
+};
+use http::{header, HeaderMap, Method, Request, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use tower::ServiceExt;
+
+use crate::{
+    adapter::AdapterManager,
+    config::{Config, Environment},
+    context::Context,
+    errors::Error,
+    health::Readiness,
+    hook::LifeCycle,
+    signal::ShutdownPhase,
+    startup::{self, BootedApp},
+    worker::Supervisor,
+    Result,
+};
+
+type ConfigOverride = Box<dyn FnOnce(&mut Config) + Send>;
+
+/// Builder of a [`TestServer`].
+///
+/// Example
+/// ```rust,ignore
+/// let server = TestServer::builder()
+///     .config(|cfg| cfg.server.interceptions.cors = None)
+///     .start::<App>()
+///     .await?;
+/// let res = server.get("/healthz").await?;
+/// assert_eq!(res.status(), 200);
+/// server.shutdown().await?;
+/// ```
+pub struct TestServerBuilder {
+    environment: Environment,
+    overrides: Vec<ConfigOverride>,
+}
+
+impl Default for TestServerBuilder {
+    fn default() -> Self {
+        Self {
+            environment: Environment::Development,
+            overrides: vec![],
+        }
+    }
+}
+
+impl TestServerBuilder {
+    /// Environment used to load the configuration, defaults to `development`.
+    #[must_use]
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Override the loaded configuration before the application is built.
+    #[must_use]
+    pub fn config<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut Config) + Send + 'static,
+    {
+        self.overrides.push(Box::new(f));
+        self
+    }
+
+    /// Build the application exactly like [`startup::run`] does, without
+    /// binding any listener.
+    pub async fn start<LC: LifeCycle>(self) -> Result<TestServer> {
+        let loader = LC::config_loader();
+        let configs = loader.load(&self.environment).map_err(Error::wrap)?;
+        let ctx = startup::create_context_from(loader, self.environment, configs, |configs| {
+            for f in self.overrides {
+                f(configs);
+            }
+        })?;
+
+        let BootedApp {
+            ctx,
+            router,
+            admin,
+            adapter_manager,
+        } = startup::boot::<LC>(ctx).await?;
+        let supervisor = startup::start_workers::<LC>(&ctx, &adapter_manager).await?;
+
+        let connect_info = MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0)));
+        Ok(TestServer {
+            ctx,
+            router: router.layer(connect_info),
+            admin: admin.map(|admin| admin.layer(connect_info)),
+            adapter_manager,
+            supervisor,
+        })
+    }
+}
+
+/// In-process application used to issue requests in tests.
+pub struct TestServer {
+    ctx: Context,
+    router: Router,
+    admin: Option<Router>,
+    adapter_manager: AdapterManager,
+    supervisor: Supervisor,
+}
+
+impl TestServer {
+    pub fn builder() -> TestServerBuilder {
+        TestServerBuilder::default()
+    }
+
+    /// Build the application with the `development` configuration.
+    pub async fn start<LC: LifeCycle>() -> Result<Self> {
+        Self::builder().start::<LC>().await
+    }
+
+    /// Context of the application after the adapters `before_run` hook.
+    pub fn ctx(&self) -> &Context {
+        &self.ctx
+    }
+
+    pub fn adapter_manager(&self) -> &AdapterManager {
+        &self.adapter_manager
+    }
+
+    /// Send a request to the application.
+    pub async fn request(&self, request: Request<Body>) -> Result<TestResponse> {
+        Self::send(self.router.clone(), request).await
+    }
+
+    /// Send a request to the admin routes of the application.
+    pub async fn admin_request(&self, request: Request<Body>) -> Result<TestResponse> {
+        let admin = self
+            .admin
+            .clone()
+            .ok_or_else(|| Error::string("the application has no admin routes"))?;
+        Self::send(admin, request).await
+    }
+
+    async fn send(router: Router, request: Request<Body>) -> Result<TestResponse> {
+        let response = router
+            .oneshot(request)
+            .await
+            .map_err(|e| Error::Message(e.to_string()))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map_err(Error::wrap)?;
+
+        Ok(TestResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    pub async fn get(&self, uri: &str) -> Result<TestResponse> {
+        self.request(Request::get(uri).body(Body::empty())?).await
+    }
+
+    pub async fn delete(&self, uri: &str) -> Result<TestResponse> {
+        self.request(Request::delete(uri).body(Body::empty())?)
+            .await
+    }
+
+    pub async fn post_json<T: Serialize>(&self, uri: &str, body: &T) -> Result<TestResponse> {
+        self.send_json(Method::POST, uri, body).await
+    }
+
+    pub async fn put_json<T: Serialize>(&self, uri: &str, body: &T) -> Result<TestResponse> {
+        self.send_json(Method::PUT, uri, body).await
+    }
+
+    async fn send_json<T: Serialize>(
+        &self,
+        method: Method,
+        uri: &str,
+        body: &T,
+    ) -> Result<TestResponse> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(body)?))?;
+        self.request(request).await
+    }
+
+    /// Run the graceful shutdown phases, the adapters `before_stop`/`after_stop`
+    /// hooks and wait for the workers, like the application does when it
+    /// receives a shutdown signal.
+    pub async fn shutdown(self) -> Result<()> {
+        if let Some(readiness) = self.ctx.get::<Readiness>() {
+            readiness.set_ready(false);
+        }
+        for phase in [
+            ShutdownPhase::PreStop,
+            ShutdownPhase::Draining,
+            ShutdownPhase::Drained { forced: false },
+        ] {
+            self.adapter_manager.notify_shutdown(phase).await?;
+        }
+        startup::shutdown(&self.adapter_manager, self.supervisor).await
+    }
+}
+
+/// Response collected by a [`TestServer`].
+#[derive(Debug)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// Deserialize the body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+/// Development configuration changed by `configure`.
+#[cfg(test)]
+pub(crate) fn config_with(configure: impl FnOnce(&mut Config)) -> Config {
+    let mut config = crate::config::load_configuration(&Environment::Development).unwrap();
+    configure(&mut config);
+    config
+}
+
+/// Context holding the [`config_with`] configuration, for the tests of the
+/// adapters.
+#[cfg(test)]
+pub(crate) fn context_with(configure: impl FnOnce(&mut Config)) -> Context {
+    Context {
+        configs: Some(config_with(configure)),
+        ..Context::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::Adapter;
+    use async_trait::async_trait;
+    use axum::{
+        extract::State,
+        routing::{get, post},
+    };
+    use serde::Deserialize;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static BEFORE_STOP: AtomicBool = AtomicBool::new(false);
+    static AFTER_STOP: AtomicBool = AtomicBool::new(false);
+    static UNHEALTHY: AtomicBool = AtomicBool::new(false);
+    static ECHOED: AtomicBool = AtomicBool::new(false);
+
+    #[derive(Debug)]
+    struct StopAdapter;
+
+    #[async_trait]
+    impl Adapter for StopAdapter {
+        fn name(&self) -> String {
+            "stop".to_string()
+        }
+
+        async fn health(&self, _ctx: &Context) -> Result<()> {
+            if UNHEALTHY.load(Ordering::SeqCst) {
+                return Err(Error::string("connection lost"));
+            }
+            Ok(())
+        }
+
+        async fn before_stop(&self, _ctx: &Context) -> Result<()> {
+            BEFORE_STOP.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn after_stop(&self, _ctx: Context) -> Result<()> {
+            // The published events are handled before `after_stop`.
+            AFTER_STOP.store(ECHOED.load(Ordering::SeqCst), Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn subscribers(&self) -> Vec<crate::event::Subscriber> {
+            vec![crate::event::Subscriber::new(
+                "echoed",
+                |_ctx, _echo: Echo| async {
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    ECHOED.store(true, Ordering::SeqCst);
+                    Ok(())
+                },
+            )]
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct Echo {
+        message: String,
+    }
+
+    async fn panics() -> &'static str {
+        panic!("handler bug")
+    }
+
+    struct MockLifeCycle;
+
+    #[async_trait]
+    impl LifeCycle for MockLifeCycle {
+        fn app_name() -> &'static str {
+            "test-app"
+        }
+
+        async fn adapters() -> Result<Vec<Box<dyn Adapter>>> {
+            Ok(vec![Box::new(StopAdapter)])
+        }
+
+        fn routes(ctx: Context) -> Router {
+            Router::new()
+                .route("/health", get(|| async { "OK" }))
+                .route(
+                    "/fail",
+                    get(|| async {
+                        Err::<(), _>(Error::InternalServerError("pool exhausted".to_string()))
+                    }),
+                )
+                .route(
+                    "/panic",
+                    get(panics),
+                )
+                .route(
+                    "/echo",
+                    post(
+                        |State(ctx): State<Context>,
+                         crate::responses::Json(echo): crate::responses::Json<Echo>| async move {
+                            crate::event::publish(&ctx, echo.clone()).await?;
+                            Ok::<_, Error>(crate::responses::Json(echo))
+                        },
+                    ),
+                )
+                .with_state(ctx)
+        }
+
+        fn admin_routes(ctx: Context) -> Option<Router> {
+            Some(
+                Router::new()
+                    .route("/version", get(|| async { "0.1.0" }))
+                    .with_state(ctx),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_lifecycle() {
+        let server = TestServer::builder()
+            .config(|cfg| cfg.server.port = 0)
+            .start::<MockLifeCycle>()
+            .await
+            .unwrap();
+        assert_eq!(server.ctx().configs.as_ref().unwrap().server.port, 0);
+        assert!(server
+            .ctx()
+            .get::<crate::timeline::StartupTimeline>()
+            .is_some());
+
+        let res = server.get("/health").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text(), "OK");
+        assert!(res.header("x-request-id").is_some());
+
+        let echo = Echo {
+            message: "hello".to_string(),
+        };
+        let res = server.post_json("/echo", &echo).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.json::<Echo>().unwrap(), echo);
+
+        let res = server.get("/missing").await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = server
+            .admin_request(Request::get("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.text(), "0.1.0");
+
+        let admin = || server.admin_request(Request::get("/readyz").body(Body::empty()).unwrap());
+        assert_eq!(admin().await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            server.get("/readyz").await.unwrap().status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let res = server
+            .admin_request(Request::get("/adapters").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let adapters: serde_json::Value = res.json().unwrap();
+        assert_eq!(adapters[0]["name"], "stop");
+        assert_eq!(adapters[0]["state"], "running");
+
+        UNHEALTHY.store(true, Ordering::SeqCst);
+        assert!(!server.adapter_manager().check_health().await);
+        let res = admin().await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let ready: serde_json::Value = res.json().unwrap();
+        assert_eq!(ready["ok"], false);
+        assert_eq!(ready["adapters"][0]["healthy"], false);
+        assert_eq!(ready["adapters"][0]["error"], "connection lost");
+
+        server.shutdown().await.unwrap();
+        assert!(BEFORE_STOP.load(Ordering::SeqCst));
+        assert!(AFTER_STOP.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_override() {
+        let started = TestServer::builder()
+            .config(|cfg| cfg.logger.level = "loud".to_string())
+            .start::<MockLifeCycle>()
+            .await;
+        let Err(Error::InvalidConfig(errors)) = started else {
+            panic!("the invalid configuration was not rejected");
+        };
+        assert_eq!(errors.0[0].path, "logger.level");
+    }
+
+    #[tokio::test]
+    async fn test_profile() {
+        let server = TestServer::start::<MockLifeCycle>().await.unwrap();
+        let res = server.get("/fail").await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            res.json::<serde_json::Value>().unwrap()["message"],
+            "pool exhausted"
+        );
+        let res = server.get("/panic").await.unwrap();
+        assert_eq!(
+            res.json::<serde_json::Value>().unwrap()["message"],
+            "handler bug"
+        );
+        server.shutdown().await.unwrap();
+
+        let server = TestServer::builder()
+            .config(|cfg| {
+                cfg.profile.production = true;
+                cfg.profile.catch_panic = Some(true);
+            })
+            .start::<MockLifeCycle>()
+            .await
+            .unwrap();
+        for uri in ["/fail", "/panic"] {
+            let res = server.get(uri).await.unwrap();
+            assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            assert_eq!(res.header("content-type"), Some("application/json"));
+            assert_eq!(
+                res.json::<serde_json::Value>().unwrap()["message"],
+                "Internal Server Error"
+            );
+        }
+        server.shutdown().await.unwrap();
+    }
+}
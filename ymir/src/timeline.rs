@@ -0,0 +1,185 @@
+//! Timeline of the application startup.
+//!
+//! Every startup phase runs in a `startup` tracing span and is recorded with
+//! its duration in the [`StartupTimeline`] of the context: context creation,
+//! configuration load, the adapters hooks, the router and its interception
+//! layers, and the listeners bind. Once the listeners are bound the summary,
+//! total boot time and slowest steps, is logged and kept in the timeline.
+use std::{
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tracing::Instrument;
+use utoipa::ToSchema;
+
+/// Number of steps listed as the slowest in the [`StartupReport`].
+const SLOWEST_STEPS: usize = 5;
+
+/// A recorded startup step.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct StartupStep {
+    pub name: String,
+    /// Start of the step in milliseconds since the startup began
+    pub offset_ms: f64,
+    pub duration_ms: f64,
+}
+
+/// Summary of the startup.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct StartupReport {
+    /// Time in milliseconds from the context creation to the listeners bind
+    pub total_ms: f64,
+    /// Steps in the order they started
+    pub steps: Vec<StartupStep>,
+    /// Slowest steps first
+    pub slowest: Vec<StartupStep>,
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "started in {:.1}ms", self.total_ms)?;
+        if !self.slowest.is_empty() {
+            let slowest: Vec<_> = self
+                .slowest
+                .iter()
+                .map(|s| format!("{} {:.1}ms", s.name, s.duration_ms))
+                .collect();
+            write!(f, ", slowest: {}", slowest.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Timeline {
+    started: Instant,
+    steps: Vec<StartupStep>,
+    report: Option<StartupReport>,
+}
+
+/// Steps of the startup, set in the context by
+/// [`crate::startup::create_context_for`].
+///
+/// Steps recorded once the startup is finished are ignored, the shutdown
+/// hooks are not part of the timeline.
+#[derive(Debug, Clone)]
+pub struct StartupTimeline(Arc<Mutex<Timeline>>);
+
+impl Default for StartupTimeline {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Timeline {
+            started: Instant::now(),
+            steps: vec![],
+            report: None,
+        })))
+    }
+}
+
+impl StartupTimeline {
+    /// Record the step `name` that began at `started` and lasted `elapsed`.
+    pub fn record(&self, name: impl Into<String>, started: Instant, elapsed: Duration) {
+        let name = name.into();
+        let duration_ms = elapsed.as_secs_f64() * 1000.0;
+        tracing::debug!(step = name, duration_ms, "startup step");
+
+        let mut timeline = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if timeline.report.is_some() {
+            return;
+        }
+        let offset_ms = started
+            .saturating_duration_since(timeline.started)
+            .as_secs_f64()
+            * 1000.0;
+        timeline.steps.push(StartupStep {
+            name,
+            offset_ms,
+            duration_ms,
+        });
+    }
+
+    /// Run `f` as the step `name`.
+    pub fn step<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        let _span = tracing::info_span!("startup", step = name).entered();
+        let started = Instant::now();
+        let value = f();
+        self.record(name, started, started.elapsed());
+        value
+    }
+
+    /// Run `future` as the step `name`.
+    pub async fn step_async<F: Future>(&self, name: &str, future: F) -> F::Output {
+        let started = Instant::now();
+        let value = future
+            .instrument(tracing::info_span!("startup", step = name))
+            .await;
+        self.record(name, started, started.elapsed());
+        value
+    }
+
+    /// Close the timeline and log its summary, only the first call reports.
+    pub fn finish(&self) -> Option<StartupReport> {
+        let mut timeline = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if timeline.report.is_some() {
+            return None;
+        }
+        let mut slowest = timeline.steps.clone();
+        slowest.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+        slowest.truncate(SLOWEST_STEPS);
+        let report = StartupReport {
+            total_ms: timeline.started.elapsed().as_secs_f64() * 1000.0,
+            steps: timeline.steps.clone(),
+            slowest,
+        };
+        tracing::info!(total_ms = report.total_ms, "{report}");
+        timeline.report = Some(report.clone());
+        Some(report)
+    }
+
+    /// Summary of the finished startup
+    pub fn report(&self) -> Option<StartupReport> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .report
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_startup_timeline() {
+        let timeline = StartupTimeline::default();
+        let value = timeline.step("config", || 42);
+        assert_eq!(value, 42);
+        timeline
+            .step_async("adapter db init", async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            })
+            .await;
+        timeline.record("router", Instant::now(), Duration::from_millis(5));
+        assert!(timeline.report().is_none());
+
+        let report = timeline.finish().unwrap();
+        let names: Vec<_> = report.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["config", "adapter db init", "router"]);
+        assert_eq!(report.slowest[0].name, "adapter db init");
+        assert_eq!(report.slowest[1].name, "router");
+        assert!(report.total_ms >= report.slowest[0].duration_ms);
+        assert!(report.to_string().starts_with(&format!(
+            "started in {:.1}ms, slowest: adapter db init",
+            report.total_ms
+        )));
+
+        // The shutdown is not part of the startup.
+        assert!(timeline.finish().is_none());
+        timeline.record("adapter db before_stop", Instant::now(), Duration::ZERO);
+        assert_eq!(timeline.report().unwrap().steps.len(), 3);
+    }
+}
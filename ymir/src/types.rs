@@ -12,6 +12,12 @@ impl Ulid {
     }
 }
 
+impl Default for Ulid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PartialSchema for Ulid {
     fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
         utoipa::openapi::schema::Object::builder()
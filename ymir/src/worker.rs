@@ -0,0 +1,396 @@
+use std::{fmt::Debug, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use tokio::{sync::broadcast, task::JoinSet};
+
+use crate::{context::Context, errors::Error, Result};
+
+/// Future returned by a single run of a worker.
+pub type WorkerFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+type WorkerFn = Arc<dyn Fn(Context, Shutdown) -> WorkerFuture + Send + Sync>;
+
+/// Exponential backoff used between two runs of a worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    /// Delay before the first restart.
+    pub initial: Duration,
+    /// Upper bound of the delay between two restarts.
+    pub max: Duration,
+    /// Maximum consecutive restarts, `None` means unlimited.
+    pub max_retries: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            max_retries: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay to wait before the given (zero based) restart attempt.
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(self.max)
+            .min(self.max)
+    }
+}
+
+/// Decide what the supervisor does when a worker returns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Run the worker once.
+    #[default]
+    Never,
+    /// Restart the worker when it returns an error or panics.
+    OnFailure(Backoff),
+    /// Restart the worker whenever it returns, until shutdown.
+    Always(Backoff),
+}
+
+/// Handle given to a worker to observe the application shutdown.
+///
+/// It listens on the same broadcast channel that
+/// [`crate::adapter::AdapterManager::stop_all`] uses to notify adapters.
+pub struct Shutdown {
+    triggered: bool,
+    rx: broadcast::Receiver<()>,
+}
+
+impl Shutdown {
+    pub fn new(rx: broadcast::Receiver<()>) -> Self {
+        Self {
+            triggered: false,
+            rx,
+        }
+    }
+
+    /// Returns `true` once the shutdown signal has been received.
+    pub fn is_triggered(&mut self) -> bool {
+        if !self.triggered {
+            self.triggered = !matches!(
+                self.rx.try_recv(),
+                Err(broadcast::error::TryRecvError::Empty)
+            );
+        }
+        self.triggered
+    }
+
+    /// New handle on the same signal. A signal sent before the call is only
+    /// queued in `self`, so the new handle copies its state.
+    pub fn subscribe(&mut self) -> Self {
+        let rx = self.rx.resubscribe();
+        Self {
+            triggered: self.is_triggered(),
+            rx,
+        }
+    }
+
+    /// Wait until the shutdown signal is received.
+    pub async fn recv(&mut self) {
+        if self.triggered {
+            return;
+        }
+        let _ = self.rx.recv().await;
+        self.triggered = true;
+    }
+}
+
+/// A named long-running task supervised next to the HTTP server.
+///
+/// Example
+/// ```rust
+/// use std::time::Duration;
+/// use ymir::worker::{Backoff, RestartPolicy, Worker};
+///
+/// let worker = Worker::new("poller", |_ctx, mut shutdown| async move {
+///     loop {
+///         tokio::select! {
+///             _ = shutdown.recv() => return Ok(()),
+///             _ = tokio::time::sleep(Duration::from_secs(1)) => {
+///                 // poll something
+///             }
+///         }
+///     }
+/// })
+/// .restart(RestartPolicy::OnFailure(Backoff::default()));
+/// assert_eq!(worker.name(), "poller");
+/// ```
+#[derive(Clone)]
+pub struct Worker {
+    name: String,
+    policy: RestartPolicy,
+    run: WorkerFn,
+}
+
+impl Debug for Worker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Worker")
+            .field("name", &self.name)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Worker {
+    pub fn new<F, Fut>(name: impl Into<String>, run: F) -> Self
+    where
+        F: Fn(Context, Shutdown) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            policy: RestartPolicy::default(),
+            run: Arc::new(move |ctx, shutdown| Box::pin(run(ctx, shutdown))),
+        }
+    }
+
+    /// Set the restart policy of the worker.
+    #[must_use]
+    pub fn restart(mut self, policy: RestartPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn policy(&self) -> &RestartPolicy {
+        &self.policy
+    }
+}
+
+/// Runs the workers and restarts them according to their [`RestartPolicy`].
+pub struct Supervisor {
+    tasks: JoinSet<()>,
+}
+
+impl Supervisor {
+    /// Spawn every worker, they are stopped when `shutdown` receives a message.
+    pub fn start(ctx: Context, workers: Vec<Worker>, shutdown: broadcast::Receiver<()>) -> Self {
+        let mut tasks = JoinSet::new();
+        let mut shutdown = Shutdown::new(shutdown);
+        if !workers.is_empty() {
+            tracing::info!(workers = ?workers.iter().map(|w| w.name.as_str()).collect::<Vec<_>>().join(","), "start worker");
+        }
+        for worker in workers {
+            tasks.spawn(supervise(ctx.clone(), worker, shutdown.subscribe()));
+        }
+        Self { tasks }
+    }
+
+    /// Number of supervised workers still running.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Wait until every worker has returned.
+    pub async fn join(mut self) {
+        while self.tasks.join_next().await.is_some() {}
+    }
+}
+
+async fn supervise(ctx: Context, worker: Worker, mut shutdown: Shutdown) {
+    let mut attempt: u32 = 0;
+
+    loop {
+        // Subscribe before checking, a signal sent in between is seen by one
+        // of the two handles.
+        let worker_shutdown = shutdown.subscribe();
+        if shutdown.is_triggered() {
+            break;
+        }
+
+        let run = (worker.run)(ctx.clone(), worker_shutdown);
+        // Run on its own task so a panic is reported as a failure.
+        let result = match tokio::spawn(run).await {
+            Ok(result) => result,
+            Err(e) => Err(Error::Message(format!("worker panicked: {e}"))),
+        };
+
+        let backoff = match (&worker.policy, &result) {
+            (RestartPolicy::Never, _) | (RestartPolicy::OnFailure(_), Ok(())) => None,
+            (RestartPolicy::OnFailure(backoff), Err(_)) | (RestartPolicy::Always(backoff), _) => {
+                Some(backoff)
+            }
+        };
+
+        match &result {
+            Ok(()) => {
+                tracing::info!(worker = worker.name, "worker finished");
+                attempt = 0;
+            }
+            Err(e) => tracing::error!(worker = worker.name, error = %e, "worker failed"),
+        }
+
+        let Some(backoff) = backoff else {
+            break;
+        };
+        if shutdown.is_triggered() {
+            break;
+        }
+        if backoff.max_retries.is_some_and(|max| attempt >= max) {
+            tracing::error!(
+                worker = worker.name,
+                attempt,
+                "worker restart limit reached"
+            );
+            break;
+        }
+
+        let delay = backoff.delay(attempt);
+        attempt = attempt.saturating_add(1);
+        tracing::warn!(worker = worker.name, attempt, ?delay, "restart worker");
+        tokio::select! {
+            _ = shutdown.recv() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+    tracing::info!(worker = worker.name, "worker stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn backoff(max_retries: Option<u32>) -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(5),
+            max_retries,
+        }
+    }
+
+    fn counting_worker(policy: RestartPolicy, fail: bool) -> (Worker, Arc<AtomicU32>) {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        let worker = Worker::new("counter", move |_ctx, _shutdown| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                if fail {
+                    Err(Error::string("boom"))
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .restart(policy);
+        (worker, runs)
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            max_retries: None,
+        };
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(2), Duration::from_millis(400));
+        assert_eq!(backoff.delay(10), Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_never_runs_once() {
+        let (tx, _) = broadcast::channel(1);
+        let (worker, runs) = counting_worker(RestartPolicy::Never, true);
+        Supervisor::start(Context::default(), vec![worker], tx.subscribe())
+            .join()
+            .await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_restarts_until_limit() {
+        let (tx, _) = broadcast::channel(1);
+        let (worker, runs) = counting_worker(RestartPolicy::OnFailure(backoff(Some(3))), true);
+        Supervisor::start(Context::default(), vec![worker], tx.subscribe())
+            .join()
+            .await;
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_stops_on_success() {
+        let (tx, _) = broadcast::channel(1);
+        let (worker, runs) = counting_worker(RestartPolicy::OnFailure(backoff(None)), false);
+        Supervisor::start(Context::default(), vec![worker], tx.subscribe())
+            .join()
+            .await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_panic_is_a_failure() {
+        let (tx, _) = broadcast::channel(1);
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        let worker = Worker::new("panic", move |_ctx, _shutdown| {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run panics");
+                }
+                Ok(())
+            }
+        })
+        .restart(RestartPolicy::OnFailure(backoff(None)));
+        Supervisor::start(Context::default(), vec![worker], tx.subscribe())
+            .join()
+            .await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_always_worker() {
+        let (tx, _) = broadcast::channel(1);
+        let worker = Worker::new("loop", |_ctx, mut shutdown| async move {
+            shutdown.recv().await;
+            Ok(())
+        })
+        .restart(RestartPolicy::Always(backoff(None)));
+        let supervisor = Supervisor::start(Context::default(), vec![worker], tx.subscribe());
+        assert_eq!(supervisor.len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), supervisor.join())
+            .await
+            .expect("worker did not stop on shutdown");
+    }
+
+    #[tokio::test]
+    async fn test_signal_sent_before_subscribe_is_kept() {
+        let (tx, rx) = broadcast::channel(1);
+        tx.send(()).unwrap();
+        let mut shutdown = Shutdown::new(rx);
+        assert!(shutdown.subscribe().is_triggered());
+
+        let worker = Worker::new("loop", |_ctx, mut shutdown| async move {
+            shutdown.recv().await;
+            Ok(())
+        })
+        .restart(RestartPolicy::Always(backoff(None)));
+        let rx = tx.subscribe();
+        tx.send(()).unwrap();
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            Supervisor::start(Context::default(), vec![worker], rx).join(),
+        )
+        .await
+        .expect("worker missed the shutdown signal");
+    }
+}